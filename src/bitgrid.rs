@@ -0,0 +1,398 @@
+use crate::solver::{blocks, settle_by};
+use crate::{BoardError, Cell, OutOfBounds, Puzzle};
+
+// one bit per cell for every line of a grid in one direction, each line padded to
+// whole words. a cell is Filled, Crossed or neither, never both
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Planes {
+    // cells per line
+    len: usize,
+    // words per line
+    words: usize,
+    filled: Vec<u64>,
+    crossed: Vec<u64>,
+}
+
+impl Planes {
+    fn new(lines: usize, len: usize) -> Self {
+        let words = len.div_ceil(64);
+        Planes {
+            len,
+            words,
+            filled: vec![0; lines * words],
+            crossed: vec![0; lines * words],
+        }
+    }
+
+    fn line(&self, l: usize) -> (&[u64], &[u64]) {
+        let range = l * self.words..(l + 1) * self.words;
+        (&self.filled[range.clone()], &self.crossed[range])
+    }
+
+    fn get(&self, l: usize, i: usize) -> Cell {
+        let w = l * self.words + i / 64;
+        let bit = 1 << (i % 64);
+        if self.filled[w] & bit != 0 {
+            Cell::Filled
+        } else if self.crossed[w] & bit != 0 {
+            Cell::Crossed
+        } else {
+            Cell::Empty
+        }
+    }
+
+    fn set(&mut self, l: usize, i: usize, cell: Cell) {
+        let w = l * self.words + i / 64;
+        let bit = 1 << (i % 64);
+        self.filled[w] &= !bit;
+        self.crossed[w] &= !bit;
+        match cell {
+            Cell::Empty => {}
+            Cell::Filled => self.filled[w] |= bit,
+            Cell::Crossed => self.crossed[w] |= bit,
+        }
+    }
+}
+
+// the cells of a puzzle as bitsets, one for filled and one for crossed cells. the rows
+// and a transposed copy of the columns are kept in sync so both are runs of whole words,
+// which lets line checks skip over 64 cells at a time and the line solver apply its
+// results a word at a time
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    rows: Planes,
+    columns: Planes,
+}
+
+impl BitGrid {
+    // an all empty grid
+    pub fn new(width: usize, height: usize) -> Self {
+        BitGrid {
+            width,
+            height,
+            rows: Planes::new(height, width),
+            columns: Planes::new(width, height),
+        }
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.height
+    }
+
+    // panics if x or y is outside the grid
+    pub fn get_cell(&self, x: usize, y: usize) -> Cell {
+        self.check_bounds(x, y);
+        self.rows.get(y, x)
+    }
+
+    // panics if x or y is outside the grid
+    pub fn set_cell(&mut self, x: usize, y: usize, cell: Cell) {
+        self.check_bounds(x, y);
+        self.rows.set(y, x, cell);
+        self.columns.set(x, y, cell);
+    }
+
+    // the cells row by row, laid out like Puzzle.array
+    pub fn to_cells(&self) -> Vec<Cell> {
+        let mut cells = Vec::with_capacity(self.width * self.height);
+        for y in 0..self.height {
+            cells.extend((0..self.width).map(|x| self.rows.get(y, x)));
+        }
+        cells
+    }
+
+    // true if the filled cells of row y match the clues
+    pub fn row_matches(&self, y: usize, clues: &[usize]) -> bool {
+        let (filled, _) = self.rows.line(y);
+        runs(filled, self.width).eq(blocks(clues))
+    }
+
+    // true if the filled cells of column x match the clues
+    pub fn column_matches(&self, x: usize, clues: &[usize]) -> bool {
+        let (filled, _) = self.columns.line(x);
+        runs(filled, self.height).eq(blocks(clues))
+    }
+
+    // same as Puzzle::verify() for these cells, false if there isn't a clue line for
+    // every row and column
+    pub fn verify(&self, row_clues: &[Vec<usize>], column_clues: &[Vec<usize>]) -> bool {
+        self.fits(row_clues, column_clues)
+            && row_clues
+                .iter()
+                .enumerate()
+                .all(|(y, clues)| self.row_matches(y, clues))
+            && column_clues
+                .iter()
+                .enumerate()
+                .all(|(x, clues)| self.column_matches(x, clues))
+    }
+
+    // line solves the grid until nothing changes, like Puzzle::solve().
+    // returns false if a line turned out to be impossible or the clues are for a grid of
+    // another size
+    pub fn solve_lines(&mut self, row_clues: &[Vec<usize>], column_clues: &[Vec<usize>]) -> bool {
+        if !self.fits(row_clues, column_clues) {
+            return false;
+        }
+        let mut rows = vec![true; self.height];
+        let mut columns = vec![true; self.width];
+
+        while rows.iter().chain(columns.iter()).any(|dirty| *dirty) {
+            for y in 0..self.height {
+                if std::mem::take(&mut rows[y])
+                    && !solve_line(
+                        &mut self.rows,
+                        &mut self.columns,
+                        y,
+                        &row_clues[y],
+                        &mut columns,
+                    )
+                {
+                    return false;
+                }
+            }
+            for x in 0..self.width {
+                if std::mem::take(&mut columns[x])
+                    && !solve_line(
+                        &mut self.columns,
+                        &mut self.rows,
+                        x,
+                        &column_clues[x],
+                        &mut rows,
+                    )
+                {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+
+    // true if there is a clue line for every row and column
+    fn fits(&self, row_clues: &[Vec<usize>], column_clues: &[Vec<usize>]) -> bool {
+        row_clues.len() == self.height && column_clues.len() == self.width
+    }
+
+    fn check_bounds(&self, x: usize, y: usize) {
+        if x >= self.width || y >= self.height {
+            panic!(
+                "{}",
+                OutOfBounds {
+                    x,
+                    y,
+                    width: self.width,
+                    height: self.height,
+                }
+            );
+        }
+    }
+}
+
+impl Puzzle {
+    // the array as a bit grid, converting back with set_from_bit_grid() gives the same array
+    pub fn to_bit_grid(&self) -> BitGrid {
+        let mut grid = BitGrid::new(self.get_width(), self.get_height());
+        for (pos, cell) in self.array.iter().enumerate() {
+            grid.set_cell(pos % grid.width, pos / grid.width, *cell);
+        }
+        grid
+    }
+
+    // replaces the array with the cells of grid, which must be the size of the puzzle
+    pub fn set_from_bit_grid(&mut self, grid: &BitGrid) -> Result<(), BoardError> {
+        let expected = (self.get_width(), self.get_height());
+        if (grid.width, grid.height) != expected {
+            return Err(BoardError::WrongDimensions {
+                expected,
+                found: (grid.width, grid.height),
+            });
+        }
+        self.replace_board(grid.to_cells())
+    }
+
+    // line solves the array like solve(), with the same deductions made on a bit grid.
+    // true if that solved the puzzle
+    pub fn solve_bits(&mut self) -> bool {
+        let mut grid = self.to_bit_grid();
+        let possible = grid.solve_lines(&self.row_clues, &self.column_clues);
+        self.array = grid.to_cells();
+        possible && grid.verify(&self.row_clues, &self.column_clues)
+    }
+}
+
+// runs settle() over line l of lines straight from its bits, the changed cells are found
+// a word at a time and written to crossing, marking the crossing lines as dirty.
+// false if the clues don't fit
+fn solve_line(
+    lines: &mut Planes,
+    crossing: &mut Planes,
+    l: usize,
+    clues: &[usize],
+    dirty: &mut [bool],
+) -> bool {
+    let solved = match settle_by(lines.len, |i| lines.get(l, i), clues) {
+        Ok(solved) => solved,
+        Err(_) => return false,
+    };
+    let mut new_filled = vec![0; lines.words];
+    let mut new_crossed = vec![0; lines.words];
+    for (i, cell) in solved.iter().enumerate() {
+        match cell {
+            Cell::Empty => {}
+            Cell::Filled => new_filled[i / 64] |= 1 << (i % 64),
+            Cell::Crossed => new_crossed[i / 64] |= 1 << (i % 64),
+        }
+    }
+
+    for w in 0..lines.words {
+        let i = l * lines.words + w;
+        let mut changed = (lines.filled[i] ^ new_filled[w]) | (lines.crossed[i] ^ new_crossed[w]);
+        lines.filled[i] = new_filled[w];
+        lines.crossed[i] = new_crossed[w];
+
+        while changed != 0 {
+            let bit = changed.trailing_zeros() as usize;
+            changed &= changed - 1;
+            let pos = w * 64 + bit;
+            crossing.set(pos, l, lines.get(l, pos));
+            dirty[pos] = true;
+        }
+    }
+    true
+}
+
+// the lengths of the runs of set bits among the first len bits, like build_clues()
+// without the 0 for an empty line
+fn runs(words: &[u64], len: usize) -> impl Iterator<Item = usize> + '_ {
+    let mut pos = 0;
+    std::iter::from_fn(move || {
+        let start = next_bit(words, pos, len, true)?;
+        let end = next_bit(words, start, len, false).unwrap_or(len);
+        pos = end;
+        Some(end - start)
+    })
+}
+
+// the first bit at or after from that is set, or clear when set is false
+fn next_bit(words: &[u64], from: usize, len: usize, set: bool) -> Option<usize> {
+    if from >= len {
+        return None;
+    }
+    let read = |w: usize| if set { words[w] } else { !words[w] };
+    let mut w = from / 64;
+    let mut word = read(w) & (u64::MAX << (from % 64));
+    loop {
+        if word != 0 {
+            let bit = w * 64 + word.trailing_zeros() as usize;
+            return if bit < len { Some(bit) } else { None };
+        }
+        w += 1;
+        if w * 64 >= len {
+            return None;
+        }
+        word = read(w);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        // wide enough for rows to span two words
+        let width = 70;
+        let cells: Vec<Cell> = (0..width * 3)
+            .map(|i| match i % 7 {
+                0 | 3 => Cell::Filled,
+                5 => Cell::Crossed,
+                _ => Cell::Empty,
+            })
+            .collect();
+        let mut p = Puzzle::from_solution_grid(cells.clone(), width).unwrap();
+
+        let grid = p.to_bit_grid();
+        assert_eq!(grid.get_cell(66, 0), Cell::Filled);
+        assert_eq!(grid.get_cell(68, 2), Cell::Crossed);
+        assert_eq!(grid.to_cells(), cells);
+        assert!(grid.verify(&p.row_clues, &p.column_clues));
+
+        p.array = vec![Cell::Empty; cells.len()];
+        p.set_from_bit_grid(&grid).unwrap();
+        assert_eq!(p.array, cells);
+
+        let other = BitGrid::new(3, 70);
+        let error = p.set_from_bit_grid(&other).err().unwrap();
+        assert_eq!(
+            error,
+            BoardError::WrongDimensions {
+                expected: (70, 3),
+                found: (3, 70)
+            }
+        );
+        assert_eq!(error.to_string(), "Expected a 70x3 board but found 3x70");
+    }
+
+    #[test]
+    fn test_runs() {
+        // bits 1..3, 60..70 and 72
+        let words = [0b110 | 0xf << 60, 0b11_1111 | 1 << 8];
+        assert_eq!(runs(&words, 80).collect::<Vec<_>>(), vec![2, 10, 1]);
+        assert_eq!(runs(&words, 65).collect::<Vec<_>>(), vec![2, 5]);
+        assert_eq!(runs(&[0], 10).count(), 0);
+    }
+
+    #[test]
+    fn test_solve_bits() {
+        let mut p = Puzzle::new();
+        for clues in [vec![3], vec![2, 2], vec![5], vec![1, 1], vec![1, 1]] {
+            p.push_clues_row(clues);
+        }
+        for clues in [vec![4], vec![3], vec![1, 1], vec![3], vec![4]] {
+            p.push_clues_column(clues);
+        }
+        let mut q = p.clone();
+        let (row_clues, column_clues) = (p.row_clues.clone(), p.column_clues.clone());
+
+        assert!(p.solve_bits());
+        assert!(q.solve());
+        assert_eq!(p.array, q.array);
+
+        // overlap stalls on this one, the exact line solver doesn't
+        let mut p = Puzzle::from_solution_string("0000100101111100110111010", 5).unwrap();
+        p.array = vec![Cell::Empty; 25];
+        let mut q = p.clone();
+        assert!(p.solve_bits());
+        assert!(q.solve());
+        assert_eq!(p.array, q.array);
+
+        // rows that span two words
+        let cells: Vec<Cell> = (0..70 * 3)
+            .map(|i| if i % 7 < 3 { Cell::Filled } else { Cell::Empty })
+            .collect();
+        let mut p = Puzzle::from_solution_grid(cells, 70).unwrap();
+        p.array = vec![Cell::Empty; 70 * 3];
+        let mut q = p.clone();
+        p.solve_bits();
+        q.solve();
+        assert_eq!(p.array, q.array);
+
+        // the middle row is a single 5, so crossing one of its cells makes it impossible
+        assert!(BitGrid::new(5, 5).solve_lines(&row_clues, &column_clues));
+        let mut grid = BitGrid::new(5, 5);
+        grid.set_cell(2, 2, Cell::Crossed);
+        assert!(!grid.solve_lines(&row_clues, &column_clues));
+
+        // clues for another size are refused instead of indexed past their end
+        let mut grid = BitGrid::new(5, 5);
+        assert!(!grid.solve_lines(&[], &[]));
+        assert!(!grid.verify(&row_clues[..4], &column_clues));
+        assert_eq!(grid, BitGrid::new(5, 5));
+    }
+}
@@ -0,0 +1,416 @@
+use crate::non::{flip_vertical, parse_clue_line, parse_goal, read_non, unquote};
+use crate::{OutOfBounds, ParseError, ParseOptions, PuzzleMetadata};
+
+// a cell of a color puzzle, Filled holds an index into the palette
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorCell {
+    Empty,
+    Filled(usize),
+    Crossed,
+}
+
+// a block of len cells that all have the same color
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ColorClue {
+    pub len: usize,
+    pub color: usize,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct PaletteColor {
+    pub name: String,
+    // the char used for this color in a goal
+    pub symbol: char,
+    pub rgb: u32,
+}
+
+impl PaletteColor {
+    pub fn new(name: &str, symbol: char, rgb: u32) -> Self {
+        PaletteColor {
+            name: name.to_string(),
+            symbol,
+            rgb,
+        }
+    }
+}
+
+// index 0 of a palette is always the background, index 1 is used for clues without a color
+pub fn default_palette() -> Vec<PaletteColor> {
+    vec![
+        PaletteColor::new("white", '0', 0xFFFFFF),
+        PaletteColor::new("black", '1', 0x000000),
+    ]
+}
+
+// a nonogram where every block has a color. blocks of the same color need a gap
+// between them but blocks of different colors may touch
+#[derive(Clone)]
+pub struct ColorPuzzle {
+    // cells row by row, the first row is the top of the picture just like in the files
+    pub array: Vec<ColorCell>,
+    pub row_clues: Vec<Vec<ColorClue>>,
+    pub column_clues: Vec<Vec<ColorClue>>,
+    pub palette: Vec<PaletteColor>,
+    pub metadata: PuzzleMetadata,
+    pub solution: Option<Vec<ColorCell>>,
+}
+
+impl ColorPuzzle {
+    pub fn new() -> Self {
+        ColorPuzzle {
+            array: vec![],
+            row_clues: vec![],
+            column_clues: vec![],
+            palette: default_palette(),
+            metadata: PuzzleMetadata::default(),
+            solution: None,
+        }
+    }
+
+    // create a color puzzle out of the same format as Puzzle::from_string(), with
+    // "color <name> <symbol> <rgb>" lines for the palette and clues like "3#red"
+    pub fn from_string(string: &str) -> Result<Self, ParseError> {
+        Self::from_string_with_options(string, ParseOptions::default())
+    }
+
+    // same as from_string() but with a diagnostics hook and other settings
+    pub fn from_string_with_options(
+        string: &str,
+        mut options: ParseOptions,
+    ) -> Result<Self, ParseError> {
+        let file = read_non(string, &mut options)?;
+
+        let mut palette = default_palette();
+        for (line, text) in &file.colors {
+            let color = parse_color(*line, text)?;
+            match palette.iter_mut().find(|c| c.name == color.name) {
+                Some(existing) => *existing = color,
+                None => palette.push(color),
+            }
+        }
+
+        let clue = |text: &str| parse_color_clue(text, &palette);
+        let mut row_clues = vec![];
+        for (line, text) in &file.rows {
+            row_clues.push(without_zeros(parse_clue_line(*line, text, clue)?));
+        }
+        let mut column_clues = vec![];
+        for (line, text) in &file.columns {
+            column_clues.push(without_zeros(parse_clue_line(*line, text, clue)?));
+        }
+
+        let solution = match &file.goal {
+            Some((line, goal)) => Some(parse_goal(
+                *line,
+                goal,
+                column_clues.len(),
+                row_clues.len(),
+                |c| match palette.iter().position(|color| color.symbol == c) {
+                    Some(0) => Some(ColorCell::Empty),
+                    Some(i) => Some(ColorCell::Filled(i)),
+                    None => None,
+                },
+            )?),
+            None => None,
+        };
+
+        let mut p = ColorPuzzle {
+            array: vec![ColorCell::Empty; row_clues.len() * column_clues.len()],
+            row_clues,
+            column_clues,
+            palette,
+            metadata: file.metadata,
+            solution,
+        };
+        if options.flip_vertical {
+            p.flip_vertical();
+        }
+
+        Ok(p)
+    }
+
+    pub fn get_pos(&self, x: usize, y: usize) -> usize {
+        y * self.column_clues.len() + x
+    }
+
+    pub fn get_width(&self) -> usize {
+        self.column_clues.len()
+    }
+
+    pub fn get_height(&self) -> usize {
+        self.row_clues.len()
+    }
+
+    // panics if x or y is outside the puzzle, see try_get_cell()
+    pub fn get_cell(&self, x: usize, y: usize) -> ColorCell {
+        match self.try_get_cell(x, y) {
+            Some(cell) => cell,
+            None => panic!("{}", self.out_of_bounds(x, y)),
+        }
+    }
+
+    // panics if x or y is outside the puzzle, see try_set_cell()
+    pub fn set_cell(&mut self, x: usize, y: usize, cell: ColorCell) {
+        if let Err(e) = self.try_set_cell(x, y, cell) {
+            panic!("{}", e);
+        }
+    }
+
+    // None if x or y is outside the puzzle
+    pub fn try_get_cell(&self, x: usize, y: usize) -> Option<ColorCell> {
+        self.checked_pos(x, y).map(|pos| self.array[pos])
+    }
+
+    // same as set_cell() but returns an error instead of panicking when x or y
+    // is outside the puzzle
+    pub fn try_set_cell(&mut self, x: usize, y: usize, cell: ColorCell) -> Result<(), OutOfBounds> {
+        let pos = self
+            .checked_pos(x, y)
+            .ok_or_else(|| self.out_of_bounds(x, y))?;
+        self.array[pos] = cell;
+        Ok(())
+    }
+
+    // the index of x, y in array, None instead of wrapping onto the next row
+    fn checked_pos(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.get_width() && y < self.get_height() {
+            Some(self.get_pos(x, y))
+        } else {
+            None
+        }
+    }
+
+    fn out_of_bounds(&self, x: usize, y: usize) -> OutOfBounds {
+        OutOfBounds {
+            x,
+            y,
+            width: self.get_width(),
+            height: self.get_height(),
+        }
+    }
+
+    // mirrors the clues, the array and the solution top to bottom
+    pub fn flip_vertical(&mut self) {
+        let width = self.get_width();
+        flip_vertical(
+            &mut self.row_clues,
+            &mut self.column_clues,
+            &mut self.array,
+            &mut self.solution,
+            width,
+        );
+    }
+
+    // builds the clues of one row or column, a new clue starts whenever the color changes
+    fn build_clues(cells: &[ColorCell]) -> Vec<ColorClue> {
+        let mut built_clues: Vec<ColorClue> = vec![];
+        let mut previous = None;
+        for cell in cells {
+            match cell {
+                ColorCell::Filled(color) => {
+                    match built_clues.last_mut() {
+                        Some(clue) if previous == Some(*color) => clue.len += 1,
+                        _ => built_clues.push(ColorClue {
+                            len: 1,
+                            color: *color,
+                        }),
+                    }
+                    previous = Some(*color);
+                }
+                _ => previous = None,
+            }
+        }
+        built_clues
+    }
+
+    fn verify_rows(&self) -> bool {
+        let width = self.get_width();
+        self.row_clues
+            .iter()
+            .enumerate()
+            .all(|(y, clues)| *clues == Self::build_clues(&self.array[y * width..(y + 1) * width]))
+    }
+
+    fn verify_columns(&self) -> bool {
+        let width = self.get_width();
+        self.column_clues.iter().enumerate().all(|(x, clues)| {
+            let cells: Vec<ColorCell> = self.array.iter().skip(x).step_by(width).copied().collect();
+            *clues == Self::build_clues(&cells)
+        })
+    }
+
+    // verifies if the puzzle is complete by comparing the array to the clues,
+    // false if the array doesn't have a cell for every clue line crossing
+    pub fn verify(&self) -> bool {
+        self.array.len() == self.get_width() * self.get_height()
+            && self.verify_rows()
+            && self.verify_columns()
+    }
+}
+
+impl Default for ColorPuzzle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// "<name> <symbol> <rgb>" or "<name> <rgb>", the rgb may start with a '#'
+fn parse_color(line: usize, text: &str) -> Result<PaletteColor, ParseError> {
+    let bad_color = || ParseError::BadColor {
+        line,
+        text: text.to_string(),
+    };
+    let parts: Vec<&str> = text.split_whitespace().collect();
+    let (name, symbol, rgb) = match parts[..] {
+        [name, symbol, rgb] => {
+            let mut chars = symbol.chars();
+            match (chars.next(), chars.next()) {
+                (Some(symbol), None) => (name, symbol, rgb),
+                _ => return Err(bad_color()),
+            }
+        }
+        [name, rgb] => (name, name.chars().next().ok_or_else(bad_color)?, rgb),
+        _ => return Err(bad_color()),
+    };
+    let rgb = u32::from_str_radix(rgb.trim_start_matches('#'), 16).map_err(|_| bad_color())?;
+
+    Ok(PaletteColor::new(&unquote(name), symbol, rgb))
+}
+
+// "3" is three cells of the first color, "3#red" or "3#r" picks a color by name or symbol
+fn parse_color_clue(text: &str, palette: &[PaletteColor]) -> Option<ColorClue> {
+    let (len, color) = match text.split_once('#') {
+        Some((len, name)) => {
+            let color = palette
+                .iter()
+                .position(|c| c.name == name || name.chars().eq(std::iter::once(c.symbol)))?;
+            (len, color)
+        }
+        None => (text, 1),
+    };
+    let len = len.trim().parse().ok()?;
+
+    // the background can't be a clue
+    if color == 0 && len > 0 {
+        return None;
+    }
+    Some(ColorClue { len, color })
+}
+
+// an empty line is written as "0"
+fn without_zeros(clues: Vec<ColorClue>) -> Vec<ColorClue> {
+    clues.into_iter().filter(|clue| clue.len > 0).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FLAG: &str = "title \"Flag\"\nwidth 3\nheight 3\ncolor white 0 FFFFFF\ncolor red r #FF0000\ncolor blue b 0000FF\n\nrows\n1#b,2#r\n0\n3#r\n\ncolumns\n1#b,1#r\n1#r,1#r\n1#r,1#r\n\ngoal \"brr000rrr\"\n";
+
+    #[test]
+    fn test_from_string() {
+        let p = ColorPuzzle::from_string(FLAG).unwrap();
+
+        assert_eq!(p.palette.len(), 4);
+        assert_eq!(p.palette[2], PaletteColor::new("red", 'r', 0xFF0000));
+        assert_eq!(p.palette[3], PaletteColor::new("blue", 'b', 0x0000FF));
+        assert_eq!(
+            p.row_clues[0],
+            vec![
+                ColorClue { len: 1, color: 3 },
+                ColorClue { len: 2, color: 2 }
+            ]
+        );
+        assert_eq!(p.row_clues[1], vec![]);
+        assert_eq!(p.array.len(), 9);
+        assert_eq!(p.metadata.title.as_deref(), Some("Flag"));
+
+        let solution = p.solution.unwrap();
+        assert_eq!(solution[0], ColorCell::Filled(3));
+        assert_eq!(solution[3], ColorCell::Empty);
+    }
+
+    #[test]
+    fn test_verify() {
+        let mut p = ColorPuzzle::from_string(FLAG).unwrap();
+        assert!(!p.verify());
+
+        p.array = p.solution.clone().unwrap();
+        assert!(p.verify());
+
+        // filling the gap joins the two red blocks of the middle column
+        p.set_cell(1, 1, ColorCell::Filled(2));
+        assert!(!p.verify());
+
+        // a board of another size never passes
+        p.array = p.solution.clone().unwrap();
+        p.array.pop();
+        assert!(!p.verify());
+        let mut q = ColorPuzzle::new();
+        q.row_clues.push(vec![ColorClue { len: 1, color: 1 }]);
+        assert!(!q.verify());
+    }
+
+    #[test]
+    fn test_bounds_checked_access() {
+        let mut p = ColorPuzzle::from_string(FLAG).unwrap();
+        assert_eq!(p.try_get_cell(2, 2), Some(ColorCell::Empty));
+        // x past the width doesn't wrap onto the next row
+        assert_eq!(p.try_get_cell(3, 0), None);
+        assert_eq!(
+            p.try_set_cell(3, 0, ColorCell::Crossed),
+            Err(OutOfBounds {
+                x: 3,
+                y: 0,
+                width: 3,
+                height: 3
+            })
+        );
+        assert_eq!(p.try_set_cell(0, 1, ColorCell::Crossed), Ok(()));
+        assert_eq!(p.get_cell(0, 1), ColorCell::Crossed);
+    }
+
+    #[test]
+    fn test_build_clues() {
+        let red = ColorCell::Filled(2);
+        let blue = ColorCell::Filled(3);
+
+        // different colors touch without a gap
+        assert_eq!(
+            ColorPuzzle::build_clues(&[red, blue, blue, ColorCell::Crossed, blue]),
+            vec![
+                ColorClue { len: 1, color: 2 },
+                ColorClue { len: 2, color: 3 },
+                ColorClue { len: 1, color: 3 }
+            ]
+        );
+        assert_eq!(ColorPuzzle::build_clues(&[ColorCell::Empty]), vec![]);
+    }
+
+    #[test]
+    fn test_plain_clues() {
+        let p = ColorPuzzle::from_string("rows\n1,1\n\ncolumns\n1\n0\n1\n").unwrap();
+        assert_eq!(p.row_clues[0][0], ColorClue { len: 1, color: 1 });
+        assert_eq!(p.column_clues[1], vec![]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            ColorPuzzle::from_string("color red rr FF0000\nrows\n1\n\ncolumns\n1\n").err(),
+            Some(ParseError::BadColor {
+                line: 1,
+                text: "red rr FF0000".to_string()
+            })
+        );
+        assert_eq!(
+            ColorPuzzle::from_string("rows\n1#green\n\ncolumns\n1\n").err(),
+            Some(ParseError::BadClue {
+                line: 2,
+                text: "1#green".to_string()
+            })
+        );
+        assert!(ColorPuzzle::from_string("rows\n1\n\ncolumns\n1\n\ngoal \"r\"").is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use crate::solver::{LineQueue, LineSolver};
+use crate::{Cell, Contradiction, Puzzle, SolveStats};
+
+// the solving techniques, from the easiest to the hardest
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Technique {
+    // cells the left-most and right-most placements of a line agree on
+    Overlap,
+    // everything a single line can tell, found by trying every placement
+    LineDp,
+    // trying a cell both ways and keeping the one that doesn't end in a contradiction
+    Probing,
+    // guessing and undoing the guess when it goes wrong
+    Backtracking,
+}
+
+// how hard a puzzle is to solve by hand, for sorting levels
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct DifficultyReport {
+    // the techniques the solve needed, easiest first
+    pub techniques: Vec<Technique>,
+    // how much work the solve took, including sweeps and guesses
+    pub stats: SolveStats,
+    // 1 for overlap alone up to 5 for a lot of guessing
+    pub stars: u8,
+    // false if the clues have more than one solution, the rating is then only for
+    // finding one of them and says little about the puzzle
+    pub unique: bool,
+}
+
+impl DifficultyReport {
+    // the hardest technique needed, None if there was nothing to solve
+    pub fn hardest(&self) -> Option<Technique> {
+        self.techniques.last().copied()
+    }
+}
+
+// more guesses than this make a backtracking puzzle 5 stars instead of 4
+const MANY_GUESSES: usize = 3;
+
+impl Puzzle {
+    // solves the clues from an empty grid, always with the easiest technique that still
+    // makes progress, and reports what it took. the array is left alone
+    pub fn rate_difficulty(&self) -> Result<DifficultyReport, Contradiction> {
+        let mut p = self.clone();
+        p.array = vec![Cell::Empty; self.array.len()];
+        let mut stats = SolveStats::default();
+        let mut used = vec![];
+
+        loop {
+            let before = p.array.clone();
+            let mut queue = LineQueue::all(p.get_height(), p.get_width());
+            if !p.propagate_with(&mut queue, &mut stats, LineSolver::Overlap) {
+                return Err(Contradiction);
+            }
+            if p.array != before {
+                used.push(Technique::Overlap);
+            }
+            if !p.array.contains(&Cell::Empty) {
+                break;
+            }
+
+            let before = p.array.clone();
+            let mut queue = LineQueue::all(p.get_height(), p.get_width());
+            if !p.propagate_with(&mut queue, &mut stats, LineSolver::Settle) {
+                return Err(Contradiction);
+            }
+            if p.array != before {
+                used.push(Technique::LineDp);
+                continue;
+            }
+
+            match p.probe(&mut stats) {
+                Some(true) => {
+                    used.push(Technique::Probing);
+                    continue;
+                }
+                Some(false) => {}
+                None => return Err(Contradiction),
+            }
+
+            used.push(Technique::Backtracking);
+            let mut solutions = vec![];
+            p.search(1, &mut stats, &mut solutions);
+            match solutions.pop() {
+                Some(solution) => p.array = solution,
+                None => return Err(Contradiction),
+            }
+            break;
+        }
+
+        if !p.verify() {
+            return Err(Contradiction);
+        }
+        stats.solved = true;
+        used.sort();
+        used.dedup();
+
+        // every deduction short of a guess is forced, so only a guessed solve can have
+        // found one of several pictures
+        let unique = !used.contains(&Technique::Backtracking) || self.is_unique();
+        let stars = match used.last() {
+            None | Some(Technique::Overlap) => 1,
+            Some(Technique::LineDp) => 2,
+            Some(Technique::Probing) => 3,
+            Some(Technique::Backtracking) if stats.guesses <= MANY_GUESSES => 4,
+            Some(Technique::Backtracking) => 5,
+        };
+        Ok(DifficultyReport {
+            techniques: used,
+            stats,
+            stars,
+            unique,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::with_clues;
+
+    #[test]
+    fn test_rate_difficulty() {
+        // overlap alone fills in the whole picture
+        let p = with_clues(
+            &[&[3], &[2, 2], &[5], &[1, 1], &[1, 1]],
+            &[&[4], &[3], &[1, 1], &[3], &[4]],
+        );
+        let report = p.rate_difficulty().unwrap();
+        assert_eq!(report.techniques, vec![Technique::Overlap]);
+        assert_eq!(report.stars, 1);
+        assert_eq!(report.stats.guesses, 0);
+        assert!(report.stats.solved);
+        assert!(report.stats.sweeps > 0);
+        assert!(report.unique);
+
+        // two diagonals share the same clues, only a guess picks one
+        let p = with_clues(&[&[1], &[1]], &[&[1], &[1]]);
+        let report = p.rate_difficulty().unwrap();
+        assert_eq!(report.hardest(), Some(Technique::Backtracking));
+        assert_eq!(report.stats.guesses, 1);
+        assert_eq!(report.stars, 4);
+        assert!(!report.unique);
+
+        let p = with_clues(&[&[2]], &[&[0], &[1]]);
+        assert_eq!(p.rate_difficulty(), Err(Contradiction));
+    }
+
+    #[test]
+    fn test_rate_harder_techniques() {
+        let p = Puzzle::from_solution_string("0000100101111100110111010", 5).unwrap();
+        let report = p.rate_difficulty().unwrap();
+        assert_eq!(
+            report.techniques,
+            vec![Technique::Overlap, Technique::LineDp]
+        );
+        assert_eq!(report.stars, 2);
+
+        let p = Puzzle::from_solution_string("0000010010011000011001000", 5).unwrap();
+        let report = p.rate_difficulty().unwrap();
+        assert_eq!(report.hardest(), Some(Technique::Probing));
+        assert_eq!(report.stars, 3);
+        assert!(report.stats.probes > 0);
+
+        // rating doesn't touch the array
+        assert_eq!(p.array, p.solution.clone().unwrap());
+    }
+}
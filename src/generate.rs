@@ -0,0 +1,115 @@
+use crate::{Cell, Puzzle};
+
+// splitmix64, small and good enough for pictures. the same seed always gives the same
+// numbers on every platform, which keeps generated puzzles reproducible
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // uniform in [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // uniform in [0, n), n must not be 0
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
+
+impl Puzzle {
+    // a random puzzle whose clues have exactly one solution, ready to play with an
+    // empty array and the picture as solution. density is the share of filled cells
+    // in the starting picture, the same arguments always give the same puzzle. a width or
+    // height of 0 gives a puzzle without cells whose lines along the other side have a 0 clue
+    pub fn generate(width: usize, height: usize, density: f64, seed: u64) -> Puzzle {
+        let mut rng = Rng(seed);
+        let density = density.clamp(0.0, 1.0);
+        let size = width * height;
+
+        loop {
+            let mut picture: Vec<Cell> = (0..size)
+                .map(|_| {
+                    if rng.next_f64() < density {
+                        Cell::Filled
+                    } else {
+                        Cell::Empty
+                    }
+                })
+                .collect();
+
+            // every flip settles one cell two solutions disagreed on, give up on the
+            // picture and draw a new one if that doesn't get anywhere
+            for _ in 0..=size {
+                let mut p = Puzzle::from_picture(picture.clone(), width, height);
+                p.array = vec![Cell::Empty; size];
+
+                let solutions = p.find_solutions(2);
+                if solutions.len() == 1 {
+                    return p;
+                }
+
+                let differing: Vec<usize> = (0..size)
+                    .filter(|pos| solutions[0][*pos] != solutions[1][*pos])
+                    .collect();
+                let pos = differing[rng.below(differing.len())];
+                picture[pos] = match picture[pos] {
+                    Cell::Filled => Cell::Empty,
+                    _ => Cell::Filled,
+                };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let p = Puzzle::generate(10, 8, 0.5, 42);
+        assert_eq!((p.get_width(), p.get_height()), (10, 8));
+        assert!(p.is_unique());
+        assert!(p.array.iter().all(|cell| *cell == Cell::Empty));
+
+        let mut solved = p.clone();
+        solved.array = p.solution.clone().unwrap();
+        assert!(solved.verify());
+
+        // the same seed gives the same puzzle, another seed a different one
+        let q = Puzzle::generate(10, 8, 0.5, 42);
+        assert_eq!(p.solution, q.solution);
+        assert_eq!(p.row_clues, q.row_clues);
+        let r = Puzzle::generate(10, 8, 0.5, 43);
+        assert_ne!(p.solution, r.solution);
+    }
+
+    #[test]
+    fn test_generate_density() {
+        let p = Puzzle::generate(5, 5, 0.0, 1);
+        assert!(p.row_clues.iter().all(|clues| *clues == vec![0]));
+
+        let p = Puzzle::generate(5, 5, 1.0, 1);
+        assert!(p.row_clues.iter().all(|clues| *clues == vec![5]));
+    }
+
+    #[test]
+    fn test_generate_zero_size() {
+        let p = Puzzle::generate(0, 3, 0.5, 1);
+        assert_eq!((p.get_width(), p.get_height()), (0, 3));
+        assert_eq!(p.row_clues, vec![vec![0]; 3]);
+        assert!(p.array.is_empty());
+
+        let p = Puzzle::generate(3, 0, 0.5, 1);
+        assert_eq!((p.get_width(), p.get_height()), (3, 0));
+        assert_eq!(p.column_clues, vec![vec![0]; 3]);
+    }
+}
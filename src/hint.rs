@@ -0,0 +1,310 @@
+use crate::solver::{blocks, overlap};
+use crate::{Cell, Puzzle};
+
+// a row or a column of the puzzle
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Line {
+    Row(usize),
+    Column(usize),
+}
+
+// why the cells of a hint are forced, from the easiest to spot to the hardest
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HintReason {
+    // every clue is already filled in, so the rest of the line is crossed
+    CompletedClue,
+    // a stretch between crosses is too short to hold any clue
+    GapTooSmall,
+    // a clue is long enough that all of its placements share some cells
+    Overlap,
+    // filled cells next to an edge or a cross pin down where a clue can go
+    EdgeForcing,
+}
+
+// the next logical move: cells in one line that the line's clues force
+#[derive(Clone, PartialEq, Debug)]
+pub struct Hint {
+    pub line: Line,
+    // x, y and the value each cell has to take
+    pub cells: Vec<(usize, usize, Cell)>,
+    pub reason: HintReason,
+}
+
+impl Hint {
+    // a short sentence for the player
+    pub fn explanation(&self) -> String {
+        let line = match self.line {
+            Line::Row(y) => format!("row {}", y + 1),
+            Line::Column(x) => format!("column {}", x + 1),
+        };
+        match self.reason {
+            HintReason::CompletedClue => format!(
+                "Every clue in {} is complete, so the remaining cells are empty",
+                line
+            ),
+            HintReason::GapTooSmall => format!(
+                "A gap in {} is too small for any of its clues, so it is empty",
+                line
+            ),
+            HintReason::Overlap => format!(
+                "However the clues in {} are placed, these cells are always filled",
+                line
+            ),
+            HintReason::EdgeForcing => format!(
+                "The filled cells in {} are close to an edge or a cross, which forces these cells",
+                line
+            ),
+        }
+    }
+
+    // the hint as a JSON object for the front end
+    pub fn to_json(&self) -> String {
+        let line = match self.line {
+            Line::Row(y) => format!("{{\"row\":{}}}", y),
+            Line::Column(x) => format!("{{\"column\":{}}}", x),
+        };
+        let reason = match self.reason {
+            HintReason::CompletedClue => "completed_clue",
+            HintReason::GapTooSmall => "gap_too_small",
+            HintReason::Overlap => "overlap",
+            HintReason::EdgeForcing => "edge_forcing",
+        };
+        let cells: Vec<String> = self
+            .cells
+            .iter()
+            .map(|(x, y, cell)| {
+                let cell = match cell {
+                    Cell::Empty => "empty",
+                    Cell::Filled => "filled",
+                    Cell::Crossed => "crossed",
+                };
+                format!("{{\"x\":{},\"y\":{},\"cell\":\"{}\"}}", x, y, cell)
+            })
+            .collect();
+
+        format!(
+            "{{\"line\":{},\"reason\":\"{}\",\"cells\":[{}],\"explanation\":\"{}\"}}",
+            line,
+            reason,
+            cells.join(","),
+            self.explanation()
+        )
+    }
+}
+
+impl Puzzle {
+    // finds the easiest deduction the line solver can make from the current array,
+    // None if no single line forces anything or a line contradicts its clues
+    pub fn next_hint(&self) -> Option<Hint> {
+        let lines: Vec<Line> = (0..self.get_height())
+            .map(Line::Row)
+            .chain((0..self.get_width()).map(Line::Column))
+            .collect();
+
+        let reasons = [
+            HintReason::CompletedClue,
+            HintReason::GapTooSmall,
+            HintReason::Overlap,
+            HintReason::EdgeForcing,
+        ];
+        for reason in reasons {
+            for line in &lines {
+                let cells = self.line_cells(*line);
+                let clues = match line {
+                    Line::Row(y) => &self.row_clues[*y],
+                    Line::Column(x) => &self.column_clues[*x],
+                };
+
+                let solved = match deduce(reason, &cells, clues) {
+                    Some(solved) => solved,
+                    None => continue,
+                };
+                let forced: Vec<(usize, usize, Cell)> = solved
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, cell)| cells[*i] == Cell::Empty && **cell != Cell::Empty)
+                    .map(|(i, cell)| match line {
+                        Line::Row(y) => (i, *y, *cell),
+                        Line::Column(x) => (*x, i, *cell),
+                    })
+                    .collect();
+                if !forced.is_empty() {
+                    return Some(Hint {
+                        line: *line,
+                        cells: forced,
+                        reason,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    fn line_cells(&self, line: Line) -> Vec<Cell> {
+        match line {
+            Line::Row(y) => self.row(y).to_vec(),
+            Line::Column(x) => self.column(x).copied().collect(),
+        }
+    }
+}
+
+// runs a single technique over a line, None if the technique doesn't apply
+fn deduce(reason: HintReason, line: &[Cell], clues: &[usize]) -> Option<Vec<Cell>> {
+    let blocks = blocks(clues);
+    match reason {
+        HintReason::CompletedClue => {
+            if self::blocks(&Puzzle::build_clues(line)) != blocks {
+                return None;
+            }
+            Some(
+                line.iter()
+                    .map(|cell| match cell {
+                        Cell::Empty => Cell::Crossed,
+                        other => *other,
+                    })
+                    .collect(),
+            )
+        }
+        HintReason::GapTooSmall => {
+            let smallest = blocks.iter().copied().min()?;
+            let mut result = line.to_vec();
+            let mut start = 0;
+            for end in 0..=line.len() {
+                if end < line.len() && line[end] != Cell::Crossed {
+                    continue;
+                }
+                let gap = &line[start..end];
+                if gap.len() < smallest && !gap.contains(&Cell::Filled) {
+                    for cell in result[start..end].iter_mut() {
+                        *cell = Cell::Crossed;
+                    }
+                }
+                start = end + 1;
+            }
+            Some(result)
+        }
+        HintReason::Overlap => {
+            // leaving the filled cells out only makes the placements looser, so
+            // whatever is still forced comes from the clue lengths alone
+            let without_filled: Vec<Cell> = line
+                .iter()
+                .map(|cell| match cell {
+                    Cell::Filled => Cell::Empty,
+                    other => *other,
+                })
+                .collect();
+            let solved = overlap(&without_filled, clues)?;
+            // keep what the user already has and only add the filled cells, the cells
+            // no clue can reach are crossed by EdgeForcing
+            Some(
+                line.iter()
+                    .zip(solved)
+                    .map(|(cell, new)| match (cell, new) {
+                        (Cell::Empty, Cell::Filled) => Cell::Filled,
+                        _ => *cell,
+                    })
+                    .collect(),
+            )
+        }
+        HintReason::EdgeForcing => overlap(line, clues),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::line;
+
+    #[test]
+    fn test_deduce() {
+        assert_eq!(
+            deduce(HintReason::CompletedClue, &line("01010"), &[1, 1]),
+            Some(line("X1X1X"))
+        );
+        assert_eq!(
+            deduce(HintReason::CompletedClue, &line("00000"), &[0]),
+            Some(line("XXXXX"))
+        );
+        assert_eq!(
+            deduce(HintReason::CompletedClue, &line("01000"), &[2]),
+            None
+        );
+
+        assert_eq!(
+            deduce(HintReason::GapTooSmall, &line("0X000X0"), &[2]),
+            Some(line("XX000XX"))
+        );
+
+        assert_eq!(
+            deduce(HintReason::Overlap, &line("00000"), &[4]),
+            Some(line("01110"))
+        );
+        // the filled cell isn't used, so only the plain overlap is found
+        assert_eq!(
+            deduce(HintReason::Overlap, &line("10000"), &[3]),
+            Some(line("10100"))
+        );
+        // the cells in front of the cross can't be reached, but that isn't an overlap
+        assert_eq!(
+            deduce(HintReason::Overlap, &line("00X0000000"), &[3, 1]),
+            Some(line("00X0010000"))
+        );
+        assert_eq!(
+            deduce(HintReason::EdgeForcing, &line("00X0000000"), &[3, 1]),
+            Some(line("XXX0010000"))
+        );
+        assert_eq!(
+            deduce(HintReason::EdgeForcing, &line("10000"), &[3]),
+            Some(line("111XX"))
+        );
+    }
+
+    #[test]
+    fn test_next_hint() {
+        let mut p = Puzzle::new();
+        p.push_clues_row(vec![3]);
+        p.push_clues_row(vec![1]);
+        p.push_clues_row(vec![0]);
+        p.push_clues_column(vec![2]);
+        p.push_clues_column(vec![1]);
+        p.push_clues_column(vec![1]);
+
+        // the empty row is the easiest move
+        let hint = p.next_hint().unwrap();
+        assert_eq!(hint.line, Line::Row(2));
+        assert_eq!(hint.reason, HintReason::CompletedClue);
+        assert_eq!(hint.cells.len(), 3);
+        assert_eq!(hint.cells[0], (0, 2, Cell::Crossed));
+
+        for (x, y, cell) in hint.cells {
+            p.set_cell(x, y, cell);
+        }
+        let hint = p.next_hint().unwrap();
+        assert_eq!(hint.line, Line::Row(0));
+        assert_eq!(hint.reason, HintReason::Overlap);
+
+        // keep taking hints until the puzzle is done
+        while let Some(hint) = p.next_hint() {
+            for (x, y, cell) in hint.cells {
+                p.set_cell(x, y, cell);
+            }
+        }
+        assert!(p.verify());
+    }
+
+    #[test]
+    fn test_to_json() {
+        let hint = Hint {
+            line: Line::Column(1),
+            cells: vec![(1, 0, Cell::Filled), (1, 2, Cell::Crossed)],
+            reason: HintReason::EdgeForcing,
+        };
+        let json = hint.to_json();
+
+        assert!(json.starts_with("{\"line\":{\"column\":1},\"reason\":\"edge_forcing\","));
+        assert!(json.contains(
+            "\"cells\":[{\"x\":1,\"y\":0,\"cell\":\"filled\"},{\"x\":1,\"y\":2,\"cell\":\"crossed\"}]"
+        ));
+        assert!(json.contains("\"explanation\":\"The filled cells in column 2"));
+    }
+}
@@ -0,0 +1,229 @@
+use crate::{Cell, Puzzle};
+
+// one cell changed by a move, pos is an index into Puzzle.array
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct CellChange {
+    pub pos: usize,
+    pub before: Cell,
+    pub after: Cell,
+}
+
+// undo and redo stacks for the moves made on a puzzle. every entry is one undo
+// step, which can hold many cells when the moves were grouped together
+#[derive(Clone, Default, Debug)]
+pub struct History {
+    undo: Vec<Vec<CellChange>>,
+    redo: Vec<Vec<CellChange>>,
+    // moves made since begin_move_group(), they become one step at end_move_group()
+    group: Option<Vec<CellChange>>,
+    // how many undo steps are kept, None keeps them all
+    limit: Option<usize>,
+}
+
+impl History {
+    pub fn with_limit(limit: usize) -> Self {
+        History {
+            limit: Some(limit),
+            ..Default::default()
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty() || self.group.as_ref().is_some_and(|g| !g.is_empty())
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.group = None;
+    }
+
+    fn record(&mut self, change: CellChange) {
+        self.redo.clear();
+        match self.group.as_mut() {
+            Some(group) => group.push(change),
+            None => self.push_step(vec![change]),
+        }
+    }
+
+    fn push_step(&mut self, step: Vec<CellChange>) {
+        if step.is_empty() {
+            return;
+        }
+        self.undo.push(step);
+        self.trim();
+    }
+
+    // drops the oldest undo steps past the limit
+    fn trim(&mut self) {
+        if let Some(limit) = self.limit {
+            let excess = self.undo.len().saturating_sub(limit);
+            self.undo.drain(..excess);
+        }
+    }
+}
+
+impl Puzzle {
+    // sets a cell and records it so it can be undone, returns false if nothing
+    // changed, assist mode turned the move down or the cell is outside the puzzle
+    pub fn apply_move(&mut self, x: usize, y: usize, cell: Cell) -> bool {
+        let pos = match self.checked_pos(x, y) {
+            Some(pos) => pos,
+            None => return false,
+        };
+        let before = self.array[pos];
+        if before == cell || !self.is_move_allowed(pos, cell) {
+            return false;
+        }
+
+        self.array[pos] = cell;
+        self.history.record(CellChange {
+            pos,
+            before,
+            after: cell,
+        });
+        true
+    }
+
+    // moves made until end_move_group() are undone and redone together, like a drag-fill
+    pub fn begin_move_group(&mut self) {
+        self.end_move_group();
+        self.history.group = Some(vec![]);
+    }
+
+    pub fn end_move_group(&mut self) {
+        if let Some(group) = self.history.group.take() {
+            self.history.push_step(group);
+        }
+    }
+
+    // reverts the last move or group of moves, returns false if there was nothing to undo
+    pub fn undo(&mut self) -> bool {
+        self.end_move_group();
+        match self.history.undo.pop() {
+            Some(step) => {
+                for change in step.iter().rev() {
+                    self.array[change.pos] = change.before;
+                }
+                self.history.redo.push(step);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // applies the last undone move or group of moves again, returns false if there was none
+    pub fn redo(&mut self) -> bool {
+        match self.history.redo.pop() {
+            Some(step) => {
+                for change in step.iter() {
+                    self.array[change.pos] = change.after;
+                }
+                self.history.push_step(step);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // how many undo steps are kept, older ones are dropped first. None keeps them all
+    pub fn set_history_limit(&mut self, limit: Option<usize>) {
+        self.history.limit = limit;
+        self.history.trim();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::with_clues;
+
+    #[test]
+    fn test_undo_redo() {
+        let mut p = with_clues(&[&[2], &[1]], &[&[2], &[1]]);
+        assert!(!p.undo());
+
+        assert!(p.apply_move(0, 0, Cell::Filled));
+        assert!(p.apply_move(1, 0, Cell::Crossed));
+        assert!(!p.apply_move(1, 0, Cell::Crossed));
+        assert_eq!(p.get_board_as_string(), "1X00");
+
+        assert!(p.undo());
+        assert_eq!(p.get_board_as_string(), "1000");
+        assert!(p.undo());
+        assert_eq!(p.get_board_as_string(), "0000");
+        assert!(!p.undo());
+
+        assert!(p.redo());
+        assert_eq!(p.get_board_as_string(), "1000");
+
+        // a new move throws away what could have been redone
+        p.apply_move(0, 1, Cell::Filled);
+        assert!(!p.redo());
+        assert_eq!(p.get_board_as_string(), "1010");
+    }
+
+    #[test]
+    fn test_move_group() {
+        let mut p = with_clues(&[&[2], &[1]], &[&[2], &[1]]);
+        p.apply_move(1, 1, Cell::Crossed);
+
+        p.begin_move_group();
+        p.apply_move(0, 0, Cell::Filled);
+        p.apply_move(1, 0, Cell::Filled);
+        p.apply_move(0, 1, Cell::Filled);
+        p.end_move_group();
+        assert!(p.verify());
+
+        assert!(p.undo());
+        assert_eq!(p.get_board_as_string(), "000X");
+        assert!(p.redo());
+        assert_eq!(p.get_board_as_string(), "111X");
+
+        // undo closes a group that is still open
+        p.begin_move_group();
+        p.apply_move(1, 1, Cell::Empty);
+        assert!(p.undo());
+        assert_eq!(p.get_board_as_string(), "111X");
+    }
+
+    #[test]
+    fn test_history_limit() {
+        let mut p = with_clues(&[&[2], &[1]], &[&[2], &[1]]);
+        p.set_history_limit(Some(2));
+
+        p.apply_move(0, 0, Cell::Filled);
+        p.apply_move(1, 0, Cell::Filled);
+        p.apply_move(0, 1, Cell::Filled);
+        assert!(p.undo());
+        assert!(p.undo());
+        assert!(!p.undo());
+        assert_eq!(p.get_board_as_string(), "1000");
+
+        p.set_history_limit(None);
+        p.redo();
+        p.redo();
+        p.set_history_limit(Some(1));
+        assert!(p.undo());
+        assert!(!p.undo());
+
+        // redoing under a smaller limit keeps to it too
+        let mut p = with_clues(&[&[2], &[1]], &[&[2], &[1]]);
+        p.apply_move(0, 0, Cell::Filled);
+        p.apply_move(1, 0, Cell::Filled);
+        p.apply_move(0, 1, Cell::Filled);
+        while p.undo() {}
+        assert_eq!(p.get_board_as_string(), "0000");
+        p.set_history_limit(Some(1));
+        assert!(p.redo());
+        assert!(p.redo());
+        assert!(p.redo());
+        assert_eq!(p.get_board_as_string(), "1110");
+        assert!(p.undo());
+        assert!(!p.undo());
+    }
+}
@@ -3,6 +3,33 @@
 
 use std::error::Error;
 
+mod bitgrid;
+mod color;
+mod difficulty;
+mod generate;
+mod hint;
+mod history;
+mod netpbm;
+mod non;
+mod render;
+mod save;
+mod solver;
+mod status;
+mod svg;
+
+pub use bitgrid::BitGrid;
+pub use color::{default_palette, ColorCell, ColorClue, ColorPuzzle, PaletteColor};
+pub use difficulty::{DifficultyReport, Technique};
+pub use hint::{Hint, HintReason, Line};
+pub use history::{CellChange, History};
+pub use netpbm::ImageError;
+pub use non::{Diagnostics, ParseError, ParseOptions, PuzzleMetadata};
+pub use render::{ColumnClueAlign, GlyphSet, RenderOptions, TextRenderer};
+pub use save::SaveError;
+pub use solver::{Contradiction, SolveStats};
+pub use status::{LineState, LineStatus};
+pub use svg::{SvgCells, SvgOptions};
+
 #[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Cell {
     Empty,
@@ -10,10 +37,106 @@ pub enum Cell {
     Crossed,
 }
 
+#[derive(Clone)]
 pub struct Puzzle {
+    // cells row by row, the first row is the top of the picture just like in the files
     pub array: Vec<Cell>,
     pub row_clues: Vec<Vec<usize>>,
     pub column_clues: Vec<Vec<usize>>,
+    pub metadata: PuzzleMetadata,
+    // the intended picture when it is known, laid out like array
+    pub solution: Option<Vec<Cell>>,
+    // moves made with apply_move() that can be undone
+    pub history: History,
+    // when set and the solution is known, set_cell() and apply_move() refuse wrong moves
+    pub assist_mode: bool,
+}
+
+// cells that disagree with the solution, as x, y pairs
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct Mistakes {
+    // filled but empty in the solution
+    pub wrongly_filled: Vec<(usize, usize)>,
+    // crossed but filled in the solution
+    pub wrongly_crossed: Vec<(usize, usize)>,
+}
+
+// why set_board_from_string() turned a string down
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum BoardError {
+    // the string holds a different number of cells than the puzzle
+    WrongLength {
+        expected: usize,
+        found: usize,
+    },
+    // a char that isn't a cell, index counts chars from the start of the string
+    InvalidChar {
+        index: usize,
+        found: char,
+    },
+    // a line of a multi-line board with the wrong number of cells, row starts at 0
+    WrongRowLength {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    // a board of another size, as (width, height)
+    WrongDimensions {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+}
+
+impl std::fmt::Display for BoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoardError::WrongLength { expected, found } => {
+                write!(f, "Expected {} cells but found {}", expected, found)
+            }
+            BoardError::InvalidChar { index, found } => {
+                write!(f, "Invalid cell '{}' at {}", found, index)
+            }
+            BoardError::WrongRowLength {
+                row,
+                expected,
+                found,
+            } => write!(f, "Row {} has {} cells instead of {}", row, found, expected),
+            BoardError::WrongDimensions { expected, found } => write!(
+                f,
+                "Expected a {}x{} board but found {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+        }
+    }
+}
+
+impl Error for BoardError {}
+
+// a cell position outside the puzzle, given to try_set_cell()
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OutOfBounds {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl std::fmt::Display for OutOfBounds {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Cell ({}, {}) is outside the {}x{} puzzle",
+            self.x, self.y, self.width, self.height
+        )
+    }
+}
+
+impl Error for OutOfBounds {}
+
+impl Mistakes {
+    pub fn is_empty(&self) -> bool {
+        self.wrongly_filled.is_empty() && self.wrongly_crossed.is_empty()
+    }
 }
 
 impl Puzzle {
@@ -22,89 +145,77 @@ impl Puzzle {
             array: vec![],
             row_clues: vec![],
             column_clues: vec![],
+            metadata: PuzzleMetadata::default(),
+            solution: None,
+            history: History::default(),
+            assist_mode: false,
         }
     }
 
-    // create a puzzle struct out of strings in this format:
-    // https://github.com/mikix/nonogram-db/blob/master/FORMAT.md
-    pub fn from_string(string: &str) -> Result<Self, Box<dyn Error>> {
-        let mut strings_iter = string.split("\n");
-        let mut row_clues = vec![];
-        let mut column_clues = vec![];
+    // create a puzzle whose clues describe the picture in array, read row by row.
+    // the array is kept as is so the new puzzle starts out solved, and also stored as solution.
+    // an empty array has no rows to count, so it always gives a puzzle of height 0
+    pub fn from_solution_grid(array: Vec<Cell>, width: usize) -> Result<Self, Box<dyn Error>> {
+        if width == 0 && !array.is_empty() {
+            return Err("Grid has cells but a width of 0".into());
+        }
+        if !array.len().is_multiple_of(width) {
+            return Err("Grid length is not a multiple of the width".into());
+        }
+        let height = array.len().checked_div(width).unwrap_or(0);
+        Ok(Self::from_picture(array, width, height))
+    }
 
-        // skip to the rows
-        loop {
-            if let Some(line) = strings_iter.next() {
-                if line == "rows" {
-                    break;
-                }
-                continue;
-            }
-            return Err("Invalid string format".into());
-        }
-        // parse rows
-        loop {
-            if let Some(row) = strings_iter.next() {
-                if row.len() < 1 {
-                    println!("break");
-                    break;
-                }
+    // same as from_solution_grid() for a picture whose height is known, so a width of 0
+    // still gives height rows. array must hold width * height cells
+    pub(crate) fn from_picture(array: Vec<Cell>, width: usize, height: usize) -> Self {
+        assert_eq!(
+            array.len(),
+            width * height,
+            "picture is not width * height cells"
+        );
 
-                println!("[{}]", row);
-                let mut clues = vec![];
-                for clue in row.split(",") {
-                    let c: usize = clue.parse()?;
-                    clues.push(c);
-                }
-                row_clues.push(clues);
-                continue;
-            }
-            break;
+        let mut row_clues = vec![];
+        for y in 0..height {
+            row_clues.push(Self::build_clues(&array[y * width..(y + 1) * width]));
         }
-
-        // skip to the rows
-        loop {
-            if let Some(line) = strings_iter.next() {
-                if line == "columns" {
-                    break;
-                }
-                continue;
-            }
-            return Err("Invalid string format".into());
-        }
-        // parse columns
-        loop {
-            if let Some(column) = strings_iter.next() {
-                if column.len() < 1 {
-                    println!("break");
-                    break;
-                }
-                println!("[{}]", column);
-                let mut clues = vec![];
-                for clue in column.split(",") {
-                    let c: usize = clue.parse()?;
-                    clues.push(c);
-                }
-                column_clues.push(clues);
-                continue;
-            }
-            break;
+        let mut column_clues = vec![];
+        for x in 0..width {
+            let column: Vec<Cell> = array.iter().skip(x).step_by(width).copied().collect();
+            column_clues.push(Self::build_clues(&column));
         }
 
-        row_clues = row_clues.into_iter().rev().collect();
-
-        let mut p = Puzzle {
-            array: vec![],
+        Puzzle {
+            solution: Some(array.clone()),
+            array,
             row_clues,
             column_clues,
-        };
-        p.recalculate_size();
+            metadata: PuzzleMetadata::default(),
+            history: History::default(),
+            assist_mode: false,
+        }
+    }
 
-        Ok(p)
+    // same as from_solution_grid() but reads the picture from a string of '0'/'1' chars
+    // like the one get_board_as_string() produces, 'X' is taken as empty
+    pub fn from_solution_string(string: &str, width: usize) -> Result<Self, Box<dyn Error>> {
+        let mut array = vec![];
+        for i in string.chars() {
+            match i {
+                '0' => array.push(Cell::Empty),
+                '1' => array.push(Cell::Filled),
+                'X' => array.push(Cell::Crossed),
+                _ => return Err(format!("Invalid character '{}' in grid", i).into()),
+            }
+        }
+        Self::from_solution_grid(array, width)
     }
 
+    // the clues changed, so a known solution no longer belongs to the puzzle
     fn recalculate_size(&mut self) {
-        self.array = vec![Cell::Empty; self.row_clues.len() * self.column_clues.len()]
+        self.array = vec![Cell::Empty; self.row_clues.len() * self.column_clues.len()];
+        self.solution = None;
+        self.history.clear();
     }
 
     pub fn push_clues_row(&mut self, clues: Vec<usize>) {
@@ -117,92 +228,6 @@ impl Puzzle {
         self.recalculate_size();
     }
 
-    // unfinished solves the current puzzle, returns true if puzzle was completable by the solver
-    fn solve(&mut self) -> bool {
-        let height = self.row_clues.len();
-        let width = self.column_clues.len();
-        let mut change_made = true;
-
-        // loop over all rows and columns until no changes can be made, then return verify()
-        while change_made {
-            // becomes true if at least any one cell is solved
-            change_made = false;
-
-            // for each row
-            for y in 0..height {
-                // get cells and clues for row y
-                let cells: Vec<&mut Cell> = self
-                    .array
-                    .iter_mut()
-                    .enumerate()
-                    .filter(|(i, _)| y * width <= *i && *i < y * width + width)
-                    .map(|(_, e)| e)
-                    .collect();
-                let clues = &self.row_clues[y];
-
-                // The functions called here are defined below
-                change_made = mathematical_approach(cells, clues);
-            }
-            // for each column
-            for x in 0..width {
-                // get vec of mutable Cell references for our column
-                // TEST THIS
-                let cells: Vec<&mut Cell> = self
-                    .array
-                    .iter_mut()
-                    .enumerate()
-                    .filter(|(i, _)| *i % width == x)
-                    .map(|(_, e)| e)
-                    .collect();
-                let clues = &self.column_clues[x];
-
-                // The functions called here are defined below
-                change_made = mathematical_approach(cells, clues);
-            }
-        }
-
-        // Solves given cells using given clues. true if at least one cell is changed, false otherwise
-        fn mathematical_approach(mut cells: Vec<&mut Cell>, clues: &Vec<usize>) -> bool {
-            let mut change_made = false;
-            // Mathematical Approach
-            // https://en.wikipedia.org/wiki/Nonogram#Mathematical_approach
-
-            /* wish this worked instead! only checking empty rows for now.
-            match cells[..] {
-                [Cell::Crossed.., Cell::Empty.., Cell::Crossed..] => {}
-            }
-            */
-            if cells.iter().all(|cell| cell == &&mut Cell::Empty) {
-                // calculate minimum number of cells needed to fulfill clues
-                let mut clues_space = clues.len() - 1;
-                for clue in clues {
-                    clues_space += *clue
-                }
-                let min_clue_size = cells.len() - clues_space;
-                // any clues bigger than backfill_distance will have cells filled
-                for (i, clue) in clues.iter().enumerate() {
-                    if clue > &min_clue_size {
-                        let cells_to_backfill = clue - &min_clue_size;
-                        // find the start of the clue counting from the left
-                        let mut clue_start = 0;
-                        for j in 0..i {
-                            clue_start += clues[j];
-                            clue_start += 1;
-                        }
-                        //backfill from the right
-                        for cell in clue_start + clue - cells_to_backfill..clue_start + clue {
-                            *cells[cell] = Cell::Filled;
-                            change_made = true;
-                        }
-                    }
-                }
-            }
-            return change_made;
-        }
-
-        return self.verify();
-    }
-
     pub fn get_pos(&self, x: usize, y: usize) -> usize {
         y * self.column_clues.len() + x
     }
@@ -215,12 +240,122 @@ impl Puzzle {
         self.row_clues.len()
     }
 
+    // panics if x or y is outside the puzzle, see try_get_cell()
     pub fn get_cell(&self, x: usize, y: usize) -> Cell {
-        self.array[self.get_pos(x, y)]
+        match self.try_get_cell(x, y) {
+            Some(cell) => cell,
+            None => panic!("{}", self.out_of_bounds(x, y)),
+        }
+    }
+
+    // returns false if assist mode turned the move down, panics if x or y is
+    // outside the puzzle, see try_set_cell()
+    pub fn set_cell(&mut self, x: usize, y: usize, cell: Cell) -> bool {
+        match self.try_set_cell(x, y, cell) {
+            Ok(allowed) => allowed,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    // None if x or y is outside the puzzle
+    pub fn try_get_cell(&self, x: usize, y: usize) -> Option<Cell> {
+        self.checked_pos(x, y).map(|pos| self.array[pos])
+    }
+
+    // same as set_cell() but returns an error instead of panicking when x or y
+    // is outside the puzzle
+    pub fn try_set_cell(&mut self, x: usize, y: usize, cell: Cell) -> Result<bool, OutOfBounds> {
+        let pos = self
+            .checked_pos(x, y)
+            .ok_or_else(|| self.out_of_bounds(x, y))?;
+        if !self.is_move_allowed(pos, cell) {
+            return Ok(false);
+        }
+        self.array[pos] = cell;
+        Ok(true)
     }
 
-    pub fn set_cell(&mut self, x: usize, y: usize, cell: Cell) {
-        self.array[y * self.column_clues.len() + x] = cell;
+    // the cells of row y from left to right, panics if y is outside the puzzle
+    pub fn row(&self, y: usize) -> &[Cell] {
+        self.check_row(y);
+        let width = self.get_width();
+        &self.array[y * width..(y + 1) * width]
+    }
+
+    // panics if y is outside the puzzle
+    pub fn row_mut(&mut self, y: usize) -> &mut [Cell] {
+        self.check_row(y);
+        let width = self.get_width();
+        &mut self.array[y * width..(y + 1) * width]
+    }
+
+    // the cells of column x from top to bottom, panics if x is outside the puzzle
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &Cell> {
+        self.check_column(x);
+        self.array.iter().skip(x).step_by(self.get_width())
+    }
+
+    // panics if x is outside the puzzle
+    pub fn column_mut(&mut self, x: usize) -> impl Iterator<Item = &mut Cell> {
+        self.check_column(x);
+        let width = self.get_width();
+        self.array.iter_mut().skip(x).step_by(width)
+    }
+
+    fn check_row(&self, y: usize) {
+        if y >= self.get_height() {
+            panic!(
+                "Row {} is outside the {}x{} puzzle",
+                y,
+                self.get_width(),
+                self.get_height()
+            );
+        }
+    }
+
+    fn check_column(&self, x: usize) {
+        if x >= self.get_width() {
+            panic!(
+                "Column {} is outside the {}x{} puzzle",
+                x,
+                self.get_width(),
+                self.get_height()
+            );
+        }
+    }
+
+    // the index of x, y in array, None instead of wrapping onto the next row
+    pub(crate) fn checked_pos(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.get_width() && y < self.get_height() {
+            Some(self.get_pos(x, y))
+        } else {
+            None
+        }
+    }
+
+    fn out_of_bounds(&self, x: usize, y: usize) -> OutOfBounds {
+        OutOfBounds {
+            x,
+            y,
+            width: self.get_width(),
+            height: self.get_height(),
+        }
+    }
+
+    // false if assist mode is on and the cell contradicts the solution
+    fn is_move_allowed(&self, pos: usize, cell: Cell) -> bool {
+        let solution = match (&self.solution, self.assist_mode) {
+            (Some(solution), true) => match solution.get(pos) {
+                Some(cell) => *cell,
+                None => return true,
+            },
+            _ => return true,
+        };
+        match cell {
+            Cell::Empty => true,
+            Cell::Filled => solution == Cell::Filled,
+            Cell::Crossed => solution != Cell::Filled,
+        }
     }
 
     pub fn get_longest_row_clue_len(&self) -> usize {
@@ -255,21 +390,68 @@ impl Puzzle {
         result
     }
 
-    pub fn set_board_from_string(&mut self, string: &str) {
+    // replaces the array with a string like the one get_board_as_string() makes,
+    // the array is left alone if the string has other chars or the wrong length
+    pub fn set_board_from_string(&mut self, string: &str) -> Result<(), BoardError> {
         let mut new_array = vec![];
-        for i in string.chars() {
+        for (index, i) in string.chars().enumerate() {
             match i {
                 '0' => new_array.push(Cell::Empty),
                 '1' => new_array.push(Cell::Filled),
                 'X' => new_array.push(Cell::Crossed),
-                _ => {}
+                _ => return Err(BoardError::InvalidChar { index, found: i }),
             }
         }
+        self.replace_board(new_array)
+    }
+
+    // same as set_board_from_string() but also takes '.' for empty, '#' for filled and
+    // 'x', '/' or '-' for crossed, and ignores whitespace. a board split into lines
+    // must have one row per line
+    pub fn set_board_from_string_lenient(&mut self, string: &str) -> Result<(), BoardError> {
+        let mut new_array = vec![];
+        let mut row = 0;
+        let mut row_start = 0;
+        for (index, i) in string.chars().enumerate() {
+            match i {
+                '0' | '.' => new_array.push(Cell::Empty),
+                '1' | '#' => new_array.push(Cell::Filled),
+                'X' | 'x' | '/' | '-' => new_array.push(Cell::Crossed),
+                '\n' => {
+                    let found = new_array.len() - row_start;
+                    if found != 0 {
+                        if found != self.get_width() {
+                            return Err(BoardError::WrongRowLength {
+                                row,
+                                expected: self.get_width(),
+                                found,
+                            });
+                        }
+                        row += 1;
+                        row_start = new_array.len();
+                    }
+                }
+                _ if i.is_whitespace() => {}
+                _ => return Err(BoardError::InvalidChar { index, found: i }),
+            }
+        }
+        self.replace_board(new_array)
+    }
+
+    fn replace_board(&mut self, new_array: Vec<Cell>) -> Result<(), BoardError> {
+        if new_array.len() != self.array.len() {
+            return Err(BoardError::WrongLength {
+                expected: self.array.len(),
+                found: new_array.len(),
+            });
+        }
         self.array = new_array;
+        self.history.clear();
+        Ok(())
     }
 
-    // verifies one row or column of rules
-    fn verify_clues(&self, clues: &Vec<usize>, cells: &[Cell]) -> bool {
+    // builds the clues of one row or column from its cells, an empty line gives [0]
+    fn build_clues(cells: &[Cell]) -> Vec<usize> {
         let mut built_clues: Vec<usize> = vec![];
         let mut mid_set = false;
         let mut current_clue: usize = 0;
@@ -287,7 +469,6 @@ impl Puzzle {
                     if mid_set {
                         mid_set = false;
                         built_clues.push(current_clue)
-                    } else {
                     }
                 }
             }
@@ -295,6 +476,12 @@ impl Puzzle {
         if mid_set || current_clue == 0 {
             built_clues.push(current_clue);
         }
+        built_clues
+    }
+
+    // verifies one row or column of rules
+    fn verify_clues(&self, clues: &[usize], cells: &[Cell]) -> bool {
+        let built_clues = Self::build_clues(cells);
 
         /*println!(
             "cells:{:?}\nclues:{:?}\nbuilt:{:?}\n",
@@ -305,10 +492,7 @@ impl Puzzle {
 
     fn verify_rows(&self) -> bool {
         for (y, v) in self.row_clues.iter().enumerate() {
-            if !self.verify_clues(
-                v,
-                &self.array[self.get_pos(0, y)..self.get_pos(self.column_clues.len(), y)],
-            ) {
+            if !self.verify_clues(v, self.row(y)) {
                 return false;
             }
         }
@@ -317,11 +501,7 @@ impl Puzzle {
 
     fn verify_columns(&self) -> bool {
         for (x, v) in self.column_clues.iter().enumerate() {
-            let mut cells = vec![];
-            for (y, _) in self.row_clues.iter().enumerate() {
-                let cell = self.array[self.get_pos(x, y)].clone();
-                cells.push(cell)
-            }
+            let cells: Vec<Cell> = self.column(x).copied().collect();
 
             if !self.verify_clues(v, &cells[..]) {
                 return false;
@@ -334,149 +514,31 @@ impl Puzzle {
     pub fn verify(&self) -> bool {
         self.verify_rows() && self.verify_columns()
     }
-}
-
-impl std::fmt::Display for Puzzle {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut string_to_print = String::new();
-
-        // figure out width of string
-        let width_of_longest_row_clue = {
-            let mut largest = 0;
-            for i in &self.row_clues {
-                if i.len() > largest {
-                    largest = i.len();
-                }
-            }
-            largest * 3
-        };
-
-        let width_of_puzzle_rows = &self.column_clues.len() * 3;
 
-        let string_width = width_of_puzzle_rows + width_of_longest_row_clue + 1; // +1 for the |'s column
-
-        // figure out height of string
-        let height_of_longest_column_clue = {
-            let mut largest = 0;
-            for i in &self.column_clues {
-                if i.len() > largest {
-                    largest = i.len();
+    // compares the array to the stored solution, None if there is no solution to compare to.
+    // empty cells are never mistakes, they just aren't done yet
+    pub fn check_against_solution(&self) -> Option<Mistakes> {
+        let solution = self.solution.as_ref()?;
+        let width = self.get_width().max(1);
+        let mut mistakes = Mistakes::default();
+
+        for (i, (cell, expected)) in self.array.iter().zip(solution).enumerate() {
+            match (cell, expected) {
+                (Cell::Filled, Cell::Filled) => {}
+                (Cell::Filled, _) => mistakes.wrongly_filled.push((i % width, i / width)),
+                (Cell::Crossed, Cell::Filled) => {
+                    mistakes.wrongly_crossed.push((i % width, i / width))
                 }
+                _ => {}
             }
-            largest
-        };
-
-        let height_of_puzzle_columns = self.row_clues.len();
-
-        let string_height = height_of_longest_column_clue + height_of_puzzle_columns + 1; // +1 for the -'s column
-
-        // fill string with spaces
-        for _ in 0..string_height {
-            for _ in 0..string_width {
-                string_to_print.push(' ');
-            }
-            string_to_print.push('\n');
-        }
-
-        // define a lambda function to replace string values with x,y coordinates
-        let mut set_string_pos = |x: usize, y: usize, c: &str| {
-            string_to_print.replace_range(
-                y * (string_width + 1) + x..y * (string_width + 1) + x + 1,
-                c,
-            )
-        };
-
-        // draw column numbers
-        for (i, clues) in self.column_clues.iter().enumerate() {
-            for (j, clue) in clues.iter().rev().enumerate() {
-                if *clue < 10 {
-                    set_string_pos(
-                        width_of_longest_row_clue + 1 + 2 + i * 3,
-                        height_of_longest_column_clue - j - 1,
-                        &clue.to_string(),
-                    );
-                } else {
-                    set_string_pos(
-                        width_of_longest_row_clue + 1 + 2 + i * 3,
-                        height_of_longest_column_clue - j - 1,
-                        &(clue / 10).to_string(),
-                    );
-                    set_string_pos(
-                        width_of_longest_row_clue + 1 + 2 + i * 3 + 1,
-                        height_of_longest_column_clue - j - 1,
-                        &(clue % 10).to_string(),
-                    );
-                }
-            }
-        }
-
-        // draw row numbers
-        for (i, clues) in self.row_clues.iter().enumerate() {
-            for (j, clue) in clues.iter().rev().enumerate() {
-                if *clue < 10 {
-                    set_string_pos(
-                        width_of_longest_row_clue - (j * 3) - 2,
-                        height_of_longest_column_clue + 1 + i,
-                        &clue.to_string(),
-                    );
-                } else {
-                    set_string_pos(
-                        width_of_longest_row_clue - (j * 3) - 2,
-                        height_of_longest_column_clue + 1 + i,
-                        &(clue / 10).to_string(),
-                    );
-                    set_string_pos(
-                        width_of_longest_row_clue - (j * 3) - 2 + 1,
-                        height_of_longest_column_clue + 1 + i,
-                        &(clue % 10).to_string(),
-                    );
-                }
-            }
-        }
-
-        // draw -'s
-        for i in 0..width_of_puzzle_rows {
-            set_string_pos(
-                i + width_of_longest_row_clue + 1,
-                height_of_longest_column_clue,
-                "-",
-            )
-        }
-
-        // draw |'s
-        for i in 0..height_of_puzzle_columns {
-            set_string_pos(
-                width_of_longest_row_clue,
-                i + height_of_longest_column_clue + 1,
-                "|",
-            )
-        }
-
-        // draw cells
-        let mut x = 0;
-        let mut y = 0;
-        for cell in &self.array {
-            if x >= self.column_clues.len() {
-                y += 1;
-                x = 0;
-            }
-            let s: &str;
-
-            match cell {
-                Cell::Empty => s = ".",
-                Cell::Filled => s = "0",
-                Cell::Crossed => s = "/",
-            }
-            set_string_pos(
-                width_of_longest_row_clue + x * 3 + 3,
-                height_of_longest_column_clue + y + 1,
-                s,
-            );
-
-            x += 1;
         }
+        Some(mistakes)
+    }
+}
 
-        write!(f, "{}", string_to_print)
+impl std::fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", TextRenderer::default().render(self))
     }
 }
 
@@ -599,22 +661,39 @@ impl Default for Puzzle {
             ],
             column_clues: vec![
                 vec![1, 1],
-                vec![4, 3],
-                vec![2, 3, 1],
+                vec![3, 4],
+                vec![1, 3, 2],
                 vec![1, 1, 1],
                 vec![1, 2, 1],
-                vec![1, 1, 2],
                 vec![2, 1, 1],
-                vec![5, 1],
+                vec![1, 1, 2],
+                vec![1, 5],
                 vec![1, 1],
                 vec![4],
             ],
+            metadata: PuzzleMetadata::default(),
+            solution: None,
+            history: History::default(),
+            assist_mode: false,
         }
     }
 }
 
 /////////////////////////////////////
 
+// a puzzle with these clues and an empty array, for the tests of every module
+#[cfg(test)]
+pub(crate) fn with_clues(rows: &[&[usize]], columns: &[&[usize]]) -> Puzzle {
+    let mut p = Puzzle::new();
+    for clues in rows {
+        p.push_clues_row(clues.to_vec());
+    }
+    for clues in columns {
+        p.push_clues_column(clues.to_vec());
+    }
+    p
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -622,11 +701,11 @@ mod tests {
     #[test]
     fn test_from_string() {
         let mut p = Puzzle::new();
-        p.push_clues_row(vec![1, 1]);
+        p.push_clues_row(vec![3]);
         p.push_clues_row(vec![1, 1]);
         p.push_clues_row(vec![0]);
         p.push_clues_row(vec![1, 1]);
-        p.push_clues_row(vec![3]);
+        p.push_clues_row(vec![1, 1]);
 
         p.push_clues_column(vec![1]);
         p.push_clues_column(vec![1, 2]);
@@ -642,14 +721,14 @@ mod tests {
 
         p = Puzzle::new();
         p.push_clues_row(vec![2]);
-        p.push_clues_row(vec![1, 2]);
+        p.push_clues_row(vec![2, 1]);
         p.push_clues_row(vec![1, 1]);
-        p.push_clues_row(vec![2]);
+        p.push_clues_row(vec![3]);
         p.push_clues_row(vec![1, 1]);
         p.push_clues_row(vec![1, 1]);
-        p.push_clues_row(vec![3]);
+        p.push_clues_row(vec![2]);
         p.push_clues_row(vec![1, 1]);
-        p.push_clues_row(vec![2, 1]);
+        p.push_clues_row(vec![1, 2]);
         p.push_clues_row(vec![2]);
         p.push_clues_column(vec![2, 1]);
         p.push_clues_column(vec![2, 1, 3]);
@@ -664,7 +743,7 @@ mod tests {
         assert_eq!(p.column_clues, pu.column_clues);
     }
 
-    //#[test]
+    #[test]
     fn test_solver_tmp() {
         let mut p = Puzzle::new();
         p.push_clues_row(vec![1, 1]);
@@ -680,9 +759,191 @@ mod tests {
 
         // [Empty, Empty, Filled, Filled, Filled, Filled, Empty, Empty, Empty, Empty, Empty, Empty, Filled, Empty, Empty]
         println!("{}", p);
-        p.solve();
+        // the top two rows can go either way, so line logic can't finish this one
+        assert!(!p.solve());
         println!("{}", p);
-        assert!(true);
+    }
+
+    #[test]
+    fn test_from_solution_grid() {
+        let p = Puzzle::from_solution_string("0101001010000001000101110", 5).unwrap();
+        assert_eq!(
+            p.row_clues,
+            vec![vec![1, 1], vec![1, 1], vec![0], vec![1, 1], vec![3]]
+        );
+        assert_eq!(
+            p.column_clues,
+            vec![vec![1], vec![2, 1], vec![1], vec![2, 1], vec![1]]
+        );
+        assert!(p.verify());
+
+        let q = Puzzle::from_solution_grid(p.array.clone(), 5).unwrap();
+        assert_eq!(p.row_clues, q.row_clues);
+        assert_eq!(p.column_clues, q.column_clues);
+        assert_eq!(p.get_board_as_string(), q.get_board_as_string());
+
+        assert!(Puzzle::from_solution_string("0101", 3).is_err());
+        assert!(Puzzle::from_solution_grid(vec![Cell::Empty; 3], 0).is_err());
+        let p = Puzzle::from_solution_grid(vec![], 4).unwrap();
+        assert_eq!((p.get_width(), p.get_height()), (4, 0));
+        assert!(Puzzle::from_solution_string("01#1", 2).is_err());
+    }
+
+    #[test]
+    fn test_bounds_checked_access() {
+        let mut p = Puzzle::from_solution_string("110001", 3).unwrap();
+
+        assert_eq!(p.try_get_cell(2, 1), Some(Cell::Filled));
+        // x past the width doesn't wrap onto the next row
+        assert_eq!(p.try_get_cell(3, 0), None);
+        assert_eq!(p.try_get_cell(0, 2), None);
+
+        assert_eq!(p.try_set_cell(2, 0, Cell::Crossed), Ok(true));
+        assert_eq!(
+            p.try_set_cell(3, 0, Cell::Filled),
+            Err(OutOfBounds {
+                x: 3,
+                y: 0,
+                width: 3,
+                height: 2
+            })
+        );
+        assert_eq!(p.get_board_as_string(), "11X001");
+
+        assert_eq!(p.row(0), &[Cell::Filled, Cell::Filled, Cell::Crossed]);
+        let column: Vec<Cell> = p.column(1).copied().collect();
+        assert_eq!(column, vec![Cell::Filled, Cell::Empty]);
+
+        // every line accessor panics the same way past the edge
+        let outside: [fn(&mut Puzzle); 4] = [
+            |p| {
+                let _ = p.row(2);
+            },
+            |p| {
+                let _ = p.row_mut(2);
+            },
+            |p| {
+                let _ = p.column(3);
+            },
+            |p| {
+                let _ = p.column_mut(3);
+            },
+        ];
+        for access in outside {
+            let mut q = p.clone();
+            assert!(std::panic::catch_unwind(move || access(&mut q)).is_err());
+        }
+
+        for cell in p.column_mut(0) {
+            *cell = Cell::Crossed;
+        }
+        p.row_mut(1)[1] = Cell::Filled;
+        assert_eq!(p.get_board_as_string(), "X1XX11");
+    }
+
+    #[test]
+    fn test_check_against_solution() {
+        let mut p = Puzzle::from_solution_string("0101001010000001000101110", 5).unwrap();
+        p.array = vec![Cell::Empty; 25];
+        assert_eq!(p.check_against_solution(), Some(Mistakes::default()));
+
+        p.set_cell(0, 0, Cell::Filled);
+        p.set_cell(1, 0, Cell::Crossed);
+        p.set_cell(3, 0, Cell::Filled);
+        p.set_cell(4, 0, Cell::Crossed);
+        let mistakes = p.check_against_solution().unwrap();
+        assert_eq!(mistakes.wrongly_filled, vec![(0, 0)]);
+        assert_eq!(mistakes.wrongly_crossed, vec![(1, 0)]);
+        assert!(!mistakes.is_empty());
+
+        p.solution = None;
+        assert_eq!(p.check_against_solution(), None);
+    }
+
+    #[test]
+    fn test_assist_mode() {
+        let mut p = Puzzle::from_solution_string("0101001010000001000101110", 5).unwrap();
+        p.array = vec![Cell::Empty; 25];
+        p.assist_mode = true;
+
+        assert!(!p.set_cell(0, 0, Cell::Filled));
+        assert!(!p.set_cell(1, 0, Cell::Crossed));
+        assert!(!p.apply_move(1, 0, Cell::Crossed));
+        assert!(p.set_cell(1, 0, Cell::Filled));
+        assert!(p.set_cell(0, 0, Cell::Crossed));
+        assert!(p.set_cell(0, 0, Cell::Empty));
+        assert_eq!(&p.get_board_as_string()[..5], "01000");
+        assert!(p.check_against_solution().unwrap().is_empty());
+
+        p.assist_mode = false;
+        assert!(p.set_cell(2, 0, Cell::Filled));
+
+        // new clues throw away the old solution instead of checking moves against it
+        let mut p =
+            Puzzle::from_string("width 1\nheight 1\n\nrows\n1\n\ncolumns\n1\n\ngoal 1\n").unwrap();
+        p.push_clues_row(vec![0]);
+        p.assist_mode = true;
+        assert!(p.solution.is_none());
+        assert!(p.set_cell(0, 1, Cell::Filled));
+
+        // a solution of the wrong size doesn't turn moves down either
+        p.solution = Some(vec![Cell::Empty]);
+        assert!(p.set_cell(0, 1, Cell::Filled));
+    }
+
+    #[test]
+    fn test_set_board_from_string() {
+        let mut p = Puzzle::from_solution_string("0110", 2).unwrap();
+        p.set_board_from_string("1X00").unwrap();
+        assert_eq!(p.get_board_as_string(), "1X00");
+
+        assert_eq!(
+            p.set_board_from_string("1X0"),
+            Err(BoardError::WrongLength {
+                expected: 4,
+                found: 3
+            })
+        );
+        assert_eq!(
+            p.set_board_from_string("1X0#"),
+            Err(BoardError::InvalidChar {
+                index: 3,
+                found: '#'
+            })
+        );
+        assert_eq!(p.get_board_as_string(), "1X00");
+    }
+
+    #[test]
+    fn test_set_board_from_string_lenient() {
+        let mut p = Puzzle::from_solution_string("011010", 3).unwrap();
+        p.set_board_from_string_lenient(".#-\n#x/\n").unwrap();
+        assert_eq!(p.get_board_as_string(), "01X1XX");
+        p.set_board_from_string_lenient("1 1 1  0 0 0").unwrap();
+        assert_eq!(p.get_board_as_string(), "111000");
+
+        assert_eq!(
+            p.set_board_from_string_lenient("..\n....\n"),
+            Err(BoardError::WrongRowLength {
+                row: 0,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            p.set_board_from_string_lenient("...\n..?"),
+            Err(BoardError::InvalidChar {
+                index: 6,
+                found: '?'
+            })
+        );
+        assert_eq!(
+            p.set_board_from_string_lenient("...\n.."),
+            Err(BoardError::WrongLength {
+                expected: 6,
+                found: 5
+            })
+        );
     }
 
     #[test]
@@ -717,9 +978,14 @@ mod tests {
             ],
             row_clues: vec![vec![1, 1], vec![1, 1], vec![0], vec![1, 1], vec![3]],
             column_clues: vec![vec![1], vec![2, 1], vec![1], vec![2, 1], vec![1]],
+            metadata: PuzzleMetadata::default(),
+            solution: None,
+            history: History::default(),
+            assist_mode: false,
         };
 
         assert!(p.verify());
+        assert!(Puzzle::default().verify());
     }
 
     #[test]
@@ -745,7 +1011,7 @@ mod tests {
         assert_eq!(p.array.len(), 25);
     }
 
-    //#[test]
+    #[test]
     fn test_solver() {
         let mut p = Puzzle::new();
         p.push_clues_row(vec![1, 1]);
@@ -754,9 +1020,9 @@ mod tests {
         p.push_clues_row(vec![1, 1]);
         p.push_clues_row(vec![3]);
         p.push_clues_column(vec![1]);
-        p.push_clues_column(vec![1, 2]);
+        p.push_clues_column(vec![2, 1]);
         p.push_clues_column(vec![1]);
-        p.push_clues_column(vec![1, 2]);
+        p.push_clues_column(vec![2, 1]);
         p.push_clues_column(vec![1]);
         p.solve();
 
@@ -790,13 +1056,34 @@ mod tests {
             ],
             row_clues: vec![vec![1, 1], vec![1, 1], vec![0], vec![1, 1], vec![3]],
             column_clues: vec![vec![1], vec![2, 1], vec![1], vec![2, 1], vec![1]],
+            metadata: PuzzleMetadata::default(),
+            solution: None,
+            history: History::default(),
+            assist_mode: false,
         };
 
-        for (i, v) in solution.array.iter().enumerate() {
+        // the top three rows are forced by line logic
+        for (i, v) in solution.array.iter().enumerate().take(15) {
             match v {
                 Cell::Filled => assert_eq!(p.array[i], *v),
                 _ => continue,
             }
         }
+        // rows 3 and 4 need more than one line at a time, but nothing
+        // the solver commits to may disagree with the solution
+        for (i, v) in p.array.iter().enumerate() {
+            match v {
+                Cell::Filled => assert_eq!(solution.array[i], Cell::Filled),
+                Cell::Crossed => assert_ne!(solution.array[i], Cell::Filled),
+                Cell::Empty => continue,
+            }
+        }
+
+        // the clues have only this one solution, and the full solver finds all of it
+        assert_eq!(p.count_solutions(2), 1);
+        p.solve_full().unwrap();
+        for (i, v) in solution.array.iter().enumerate() {
+            assert_eq!(p.array[i] == Cell::Filled, *v == Cell::Filled);
+        }
     }
 }
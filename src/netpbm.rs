@@ -0,0 +1,310 @@
+use crate::{Cell, Puzzle};
+
+// why a Netpbm image couldn't be turned into a puzzle
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ImageError {
+    // not a P1, P2, P4 or P5 file
+    UnsupportedFormat,
+    // the width, height or maxval is missing or not a number
+    BadHeader,
+    // the pixels stop before the image is complete
+    Truncated,
+    // a plain pixel that isn't a number or is above the maxval, index counts pixels
+    BadPixel { index: usize },
+}
+
+impl std::fmt::Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::UnsupportedFormat => write!(f, "Not a PBM or PGM image"),
+            ImageError::BadHeader => write!(f, "Invalid image header"),
+            ImageError::Truncated => write!(f, "Image data is truncated"),
+            ImageError::BadPixel { index } => write!(f, "Invalid pixel at {}", index),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl Puzzle {
+    // creates a puzzle from a plain or raw PBM/PGM image. black pixels and grays darker
+    // than half the maxval are filled, the picture is kept as solution and the array
+    // starts out empty
+    pub fn from_netpbm(bytes: &[u8]) -> Result<Self, ImageError> {
+        Self::from_netpbm_with_threshold(bytes, 0.5)
+    }
+
+    // same as from_netpbm() but a gray is filled when it is darker than threshold times
+    // the maxval, so 0.0 leaves every gray empty and 1.0 fills all but pure white
+    pub fn from_netpbm_with_threshold(bytes: &[u8], threshold: f64) -> Result<Self, ImageError> {
+        let mut reader = Reader { bytes, pos: 0 };
+        let magic = reader.token().ok_or(ImageError::UnsupportedFormat)?;
+        let (plain, gray) = match magic {
+            b"P1" => (true, false),
+            b"P2" => (true, true),
+            b"P4" => (false, false),
+            b"P5" => (false, true),
+            _ => return Err(ImageError::UnsupportedFormat),
+        };
+        let width = reader.number()?;
+        let height = reader.number()?;
+        let maxval = if gray { reader.number()? } else { 1 };
+        if maxval == 0 || maxval > u16::MAX as usize {
+            return Err(ImageError::BadHeader);
+        }
+
+        let size = width.checked_mul(height).ok_or(ImageError::BadHeader)?;
+        // every pixel takes at least a byte, except in a P4 where it takes a bit. checked
+        // before anything is allocated so a made up header can't ask for gigabytes
+        let needed = match (plain, gray) {
+            (false, false) => width.div_ceil(8).checked_mul(height),
+            (false, true) if maxval > 255 => size.checked_mul(2),
+            _ => Some(size),
+        };
+        if needed.is_none_or(|needed| needed > reader.remaining()) {
+            return Err(ImageError::Truncated);
+        }
+
+        let values = match (plain, gray) {
+            (true, false) => reader.plain_bits(size)?,
+            (true, true) => reader.plain_grays(size, maxval)?,
+            (false, false) => reader.raw_bits(width, height)?,
+            (false, true) => reader.raw_grays(size, maxval)?,
+        };
+
+        let limit = threshold * maxval as f64;
+        let picture: Vec<Cell> = values
+            .into_iter()
+            .map(|value| {
+                // in a PBM 1 is black, in a PGM 0 is
+                let dark = if gray {
+                    (value as f64) < limit
+                } else {
+                    value == 1
+                };
+                if dark {
+                    Cell::Filled
+                } else {
+                    Cell::Empty
+                }
+            })
+            .collect();
+
+        let mut p = Puzzle::from_picture(picture, width, height);
+        p.array = vec![Cell::Empty; size];
+        Ok(p)
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    // skips whitespace and comments, which run from a '#' to the end of the line
+    fn skip_space(&mut self) {
+        while let Some(byte) = self.bytes.get(self.pos) {
+            match byte {
+                b'#' => {
+                    while self.bytes.get(self.pos).is_some_and(|b| *b != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                b if b.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+    }
+
+    fn token(&mut self) -> Option<&'a [u8]> {
+        self.skip_space();
+        let start = self.pos;
+        while self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| !b.is_ascii_whitespace() && *b != b'#')
+        {
+            self.pos += 1;
+        }
+        (self.pos > start).then(|| &self.bytes[start..self.pos])
+    }
+
+    fn number(&mut self) -> Result<usize, ImageError> {
+        let token = self.token().ok_or(ImageError::BadHeader)?;
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|text| text.parse().ok())
+            .ok_or(ImageError::BadHeader)
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len().saturating_sub(self.pos)
+    }
+
+    // the raster of a raw file starts after a single whitespace char
+    fn raw_data(&mut self, len: usize) -> Result<&'a [u8], ImageError> {
+        let start = self.pos + 1;
+        self.bytes
+            .get(start..start + len)
+            .ok_or(ImageError::Truncated)
+    }
+
+    // P1 pixels are 0s and 1s which don't need whitespace between them
+    fn plain_bits(&mut self, size: usize) -> Result<Vec<usize>, ImageError> {
+        let mut values = vec![];
+        for index in 0..size {
+            self.skip_space();
+            values.push(match self.bytes.get(self.pos) {
+                Some(b'0') => 0,
+                Some(b'1') => 1,
+                Some(_) => return Err(ImageError::BadPixel { index }),
+                None => return Err(ImageError::Truncated),
+            });
+            self.pos += 1;
+        }
+        Ok(values)
+    }
+
+    // P2 pixels are numbers between whitespace
+    fn plain_grays(&mut self, size: usize, maxval: usize) -> Result<Vec<usize>, ImageError> {
+        let mut values = vec![];
+        for index in 0..size {
+            let token = self.token().ok_or(ImageError::Truncated)?;
+            let value = std::str::from_utf8(token)
+                .ok()
+                .and_then(|text| text.parse().ok())
+                .filter(|value| *value <= maxval)
+                .ok_or(ImageError::BadPixel { index })?;
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    // P4 packs 8 pixels into a byte, most significant bit first, every row starts a byte
+    fn raw_bits(&mut self, width: usize, height: usize) -> Result<Vec<usize>, ImageError> {
+        let row_bytes = width.div_ceil(8);
+        let data = self.raw_data(row_bytes * height)?;
+        let mut values = vec![];
+        for row in data.chunks(row_bytes.max(1)).take(height) {
+            for x in 0..width {
+                values.push((row[x / 8] >> (7 - x % 8) & 1) as usize);
+            }
+        }
+        Ok(values)
+    }
+
+    // P5 has a byte per pixel, or two big endian bytes when the maxval is over 255
+    fn raw_grays(&mut self, size: usize, maxval: usize) -> Result<Vec<usize>, ImageError> {
+        if maxval < 256 {
+            let data = self.raw_data(size)?;
+            Ok(data.iter().map(|b| *b as usize).collect())
+        } else {
+            let data = self.raw_data(size * 2)?;
+            Ok(data
+                .chunks(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]) as usize)
+                .collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain() {
+        let pbm = b"P1\n# a comment\n3 2\n0 1 0\n101\n";
+        let p = Puzzle::from_netpbm(pbm).unwrap();
+        assert_eq!((p.get_width(), p.get_height()), (3, 2));
+        assert_eq!(p.row_clues, vec![vec![1], vec![1, 1]]);
+        assert_eq!(p.column_clues, vec![vec![1], vec![1], vec![1]]);
+        assert_eq!(p.array, vec![Cell::Empty; 6]);
+
+        let pgm = b"P2 4 1 255\n0 100 200 255\n";
+        let p = Puzzle::from_netpbm(pgm).unwrap();
+        assert_eq!(p.row_clues, vec![vec![2]]);
+        let p = Puzzle::from_netpbm_with_threshold(pgm, 0.9).unwrap();
+        assert_eq!(p.row_clues, vec![vec![3]]);
+    }
+
+    #[test]
+    fn test_raw() {
+        // 10 pixels wide, so every row takes two bytes
+        let mut pbm = b"P4\n10 2\n".to_vec();
+        pbm.extend_from_slice(&[0b1100_0000, 0b0100_0000, 0b0000_0000, 0b1000_0000]);
+        let p = Puzzle::from_netpbm(&pbm).unwrap();
+        assert_eq!(p.row_clues, vec![vec![2, 1], vec![1]]);
+        let solution = p.solution.unwrap();
+        assert_eq!(solution[9], Cell::Filled);
+        assert_eq!(solution[18], Cell::Filled);
+
+        let mut pgm = b"P5 3 1 255\n".to_vec();
+        pgm.extend_from_slice(&[10, 250, 10]);
+        let p = Puzzle::from_netpbm(&pgm).unwrap();
+        assert_eq!(p.row_clues, vec![vec![1, 1]]);
+
+        let mut pgm = b"P5 2 1 65535\n".to_vec();
+        pgm.extend_from_slice(&[0xff, 0x00, 0x10, 0x00]);
+        let p = Puzzle::from_netpbm(&pgm).unwrap();
+        assert_eq!(p.row_clues, vec![vec![1]]);
+        assert_eq!(p.solution.unwrap()[1], Cell::Filled);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Puzzle::from_netpbm(b"P3 1 1 255 0 0 0").err(),
+            Some(ImageError::UnsupportedFormat)
+        );
+        assert_eq!(
+            Puzzle::from_netpbm(b"P1 2 x\n01").err(),
+            Some(ImageError::BadHeader)
+        );
+        assert_eq!(
+            Puzzle::from_netpbm(b"P1 2 2\n010").err(),
+            Some(ImageError::Truncated)
+        );
+        assert_eq!(
+            Puzzle::from_netpbm(b"P1 2 1\n02").err(),
+            Some(ImageError::BadPixel { index: 1 })
+        );
+        assert_eq!(
+            Puzzle::from_netpbm(b"P2 2 1 15\n3 16").err(),
+            Some(ImageError::BadPixel { index: 1 })
+        );
+        assert_eq!(
+            Puzzle::from_netpbm(b"P5 2 1 255\n\x00").err(),
+            Some(ImageError::Truncated)
+        );
+    }
+
+    #[test]
+    fn test_huge_header() {
+        // neither allocates room for the pixels the header promises
+        assert_eq!(
+            Puzzle::from_netpbm(b"P1 100000 100000\n0").err(),
+            Some(ImageError::Truncated)
+        );
+        assert_eq!(
+            Puzzle::from_netpbm(b"P5 4000000000 4000000000 65535\n\x00\x00").err(),
+            Some(ImageError::Truncated)
+        );
+        assert_eq!(
+            Puzzle::from_netpbm(b"P4 100000 100000\n\x00").err(),
+            Some(ImageError::Truncated)
+        );
+    }
+
+    #[test]
+    fn test_zero_size() {
+        // no pixels, but the image still has 5 rows
+        let p = Puzzle::from_netpbm(b"P1 0 5\n").unwrap();
+        assert_eq!((p.get_width(), p.get_height()), (0, 5));
+        assert_eq!(p.row_clues, vec![vec![0]; 5]);
+
+        let p = Puzzle::from_netpbm(b"P5 3 0 255\n").unwrap();
+        assert_eq!((p.get_width(), p.get_height()), (3, 0));
+    }
+}
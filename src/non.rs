@@ -0,0 +1,628 @@
+use std::fmt::Write;
+use std::iter::Enumerate;
+use std::str::Lines;
+
+use crate::{Cell, History, Puzzle};
+
+// the descriptive fields of a nonogram-db file, any of them may be missing
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct PuzzleMetadata {
+    pub catalogue: Option<String>,
+    pub title: Option<String>,
+    pub by: Option<String>,
+    pub copyright: Option<String>,
+    pub license: Option<String>,
+}
+
+// what went wrong reading a nonogram-db file, line numbers start at 1
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    // the file ended without a "rows" section
+    MissingRowsSection {
+        line: usize,
+    },
+    // the file ended without a "columns" section
+    MissingColumnsSection {
+        line: usize,
+    },
+    // a clue line that isn't a comma separated list of clues
+    BadClue {
+        line: usize,
+        text: String,
+    },
+    // a width or height that isn't a number
+    BadNumber {
+        line: usize,
+        text: String,
+    },
+    // a goal with a char that isn't '0', '1' or a color symbol
+    BadGoal {
+        line: usize,
+        text: String,
+    },
+    // a color line that isn't "<name> <symbol> <rgb>"
+    BadColor {
+        line: usize,
+        text: String,
+    },
+    // width or height don't agree with the clues
+    DimensionMismatch {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    // a goal without a cell for every cell of the grid the clues give
+    GoalLengthMismatch {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl ParseError {
+    pub fn line(&self) -> usize {
+        match self {
+            ParseError::MissingRowsSection { line }
+            | ParseError::MissingColumnsSection { line }
+            | ParseError::BadClue { line, .. }
+            | ParseError::BadNumber { line, .. }
+            | ParseError::BadGoal { line, .. }
+            | ParseError::BadColor { line, .. }
+            | ParseError::DimensionMismatch { line, .. }
+            | ParseError::GoalLengthMismatch { line, .. } => *line,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseError::MissingRowsSection { line } => {
+                write!(f, "line {}: missing \"rows\" section", line)
+            }
+            ParseError::MissingColumnsSection { line } => {
+                write!(f, "line {}: missing \"columns\" section", line)
+            }
+            ParseError::BadClue { line, text } => write!(f, "line {}: bad clue \"{}\"", line, text),
+            ParseError::BadNumber { line, text } => {
+                write!(f, "line {}: bad number \"{}\"", line, text)
+            }
+            ParseError::BadGoal { line, text } => write!(f, "line {}: bad goal \"{}\"", line, text),
+            ParseError::BadColor { line, text } => {
+                write!(f, "line {}: bad color \"{}\"", line, text)
+            }
+            ParseError::DimensionMismatch {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: expected {} but the clues give {}",
+                line, expected, found
+            ),
+            ParseError::GoalLengthMismatch {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: goal has {} cells but the clues give {}",
+                line, found, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// receives the line number and a short note for every line the parser reads
+pub type Diagnostics<'a> = &'a mut dyn FnMut(usize, &str);
+
+// settings for from_string_with_options(), the defaults match from_string()
+#[derive(Default)]
+pub struct ParseOptions<'a> {
+    pub diagnostics: Option<Diagnostics<'a>>,
+    // mirror the puzzle top to bottom, so the last row of the file becomes row 0.
+    // older versions stored the rows of a file in this order
+    pub flip_vertical: bool,
+}
+
+impl ParseOptions<'_> {
+    fn trace(&mut self, line: usize, message: &str) {
+        if let Some(diagnostics) = self.diagnostics.as_mut() {
+            diagnostics(line, message);
+        }
+    }
+}
+
+impl Puzzle {
+    // create a puzzle struct out of strings in this format:
+    // https://github.com/mikix/nonogram-db/blob/master/FORMAT.md
+    pub fn from_string(string: &str) -> Result<Self, ParseError> {
+        Self::from_string_with_options(string, ParseOptions::default())
+    }
+
+    // same as from_string() but with a diagnostics hook and other settings
+    pub fn from_string_with_options(
+        string: &str,
+        mut options: ParseOptions,
+    ) -> Result<Self, ParseError> {
+        let file = read_non(string, &mut options)?;
+
+        let mut row_clues = vec![];
+        for (line, text) in &file.rows {
+            row_clues.push(parse_clue_line(*line, text, |clue| clue.parse().ok())?);
+        }
+        let mut column_clues = vec![];
+        for (line, text) in &file.columns {
+            column_clues.push(parse_clue_line(*line, text, |clue| clue.parse().ok())?);
+        }
+
+        let solution = match &file.goal {
+            Some((line, goal)) => Some(parse_goal(
+                *line,
+                goal,
+                column_clues.len(),
+                row_clues.len(),
+                |c| match c {
+                    '0' => Some(Cell::Empty),
+                    '1' => Some(Cell::Filled),
+                    _ => None,
+                },
+            )?),
+            None => None,
+        };
+
+        let mut p = Puzzle {
+            array: vec![Cell::Empty; row_clues.len() * column_clues.len()],
+            row_clues,
+            column_clues,
+            metadata: file.metadata,
+            solution,
+            history: History::default(),
+            assist_mode: false,
+        };
+        if options.flip_vertical {
+            p.flip_vertical();
+        }
+
+        Ok(p)
+    }
+
+    // writes the puzzle out in the same format from_string() reads
+    pub fn to_non_string(&self) -> String {
+        let mut result = String::new();
+
+        let quoted = [
+            ("catalogue", &self.metadata.catalogue),
+            ("title", &self.metadata.title),
+            ("by", &self.metadata.by),
+            ("copyright", &self.metadata.copyright),
+        ];
+        for (key, value) in quoted {
+            if let Some(value) = value {
+                let _ = writeln!(result, "{} \"{}\"", key, value);
+            }
+        }
+        if let Some(license) = &self.metadata.license {
+            let _ = writeln!(result, "license {}", license);
+        }
+        let _ = writeln!(result, "width {}", self.get_width());
+        let _ = writeln!(result, "height {}", self.get_height());
+
+        result.push_str("\nrows\n");
+        for clues in &self.row_clues {
+            result.push_str(&clues_to_string(clues));
+            result.push('\n');
+        }
+        result.push_str("\ncolumns\n");
+        for clues in &self.column_clues {
+            result.push_str(&clues_to_string(clues));
+            result.push('\n');
+        }
+
+        if let Some(solution) = &self.solution {
+            result.push_str("\ngoal \"");
+            for cell in solution {
+                result.push(if *cell == Cell::Filled { '1' } else { '0' });
+            }
+            result.push_str("\"\n");
+        }
+
+        result
+    }
+
+    // mirrors the clues, the array and the solution top to bottom
+    pub fn flip_vertical(&mut self) {
+        let width = self.get_width();
+        flip_vertical(
+            &mut self.row_clues,
+            &mut self.column_clues,
+            &mut self.array,
+            &mut self.solution,
+            width,
+        );
+    }
+}
+
+// the parts of a nonogram-db file, clue lines are kept as text so
+// Puzzle and ColorPuzzle can each read their own kind of clue
+pub(crate) struct NonFile<'a> {
+    pub(crate) metadata: PuzzleMetadata,
+    pub(crate) rows: Vec<(usize, &'a str)>,
+    pub(crate) columns: Vec<(usize, &'a str)>,
+    pub(crate) colors: Vec<(usize, &'a str)>,
+    pub(crate) goal: Option<(usize, String)>,
+}
+
+// splits a file into its parts and checks width and height against the clue lines
+pub(crate) fn read_non<'a>(
+    string: &'a str,
+    options: &mut ParseOptions,
+) -> Result<NonFile<'a>, ParseError> {
+    let mut strings_iter = string.lines().enumerate();
+    let last_line = string.lines().count().max(1);
+    let mut rows = None;
+    let mut columns = None;
+    let mut colors = vec![];
+    let mut metadata = PuzzleMetadata::default();
+    let mut width = None;
+    let mut height = None;
+    let mut goal = None;
+
+    while let Some((i, line)) = strings_iter.next() {
+        let line = line.trim();
+        let (key, value) = match line.split_once(char::is_whitespace) {
+            Some((key, value)) => (key, value.trim()),
+            None => (line, ""),
+        };
+        options.trace(i + 1, line);
+
+        match key {
+            "catalogue" => metadata.catalogue = Some(unquote(value)),
+            "title" => metadata.title = Some(unquote(value)),
+            "by" => metadata.by = Some(unquote(value)),
+            "copyright" => metadata.copyright = Some(unquote(value)),
+            "license" => metadata.license = Some(unquote(value)),
+            "width" => width = Some((i + 1, parse_number(i + 1, value)?)),
+            "height" => height = Some((i + 1, parse_number(i + 1, value)?)),
+            "color" => colors.push((i + 1, value)),
+            "rows" => rows = Some(read_section(&mut strings_iter, options)),
+            "columns" => columns = Some(read_section(&mut strings_iter, options)),
+            "goal" => goal = Some((i + 1, unquote(value))),
+            _ => {}
+        }
+    }
+
+    let rows = rows.ok_or(ParseError::MissingRowsSection { line: last_line })?;
+    let columns = columns.ok_or(ParseError::MissingColumnsSection { line: last_line })?;
+
+    if let Some((line, width)) = width {
+        if width != columns.len() {
+            return Err(ParseError::DimensionMismatch {
+                line,
+                expected: width,
+                found: columns.len(),
+            });
+        }
+    }
+    if let Some((line, height)) = height {
+        if height != rows.len() {
+            return Err(ParseError::DimensionMismatch {
+                line,
+                expected: height,
+                found: rows.len(),
+            });
+        }
+    }
+
+    Ok(NonFile {
+        metadata,
+        rows,
+        columns,
+        colors,
+        goal,
+    })
+}
+
+// collects the clue lines of a section until a blank line or the end of the string
+fn read_section<'a>(
+    strings_iter: &mut Enumerate<Lines<'a>>,
+    options: &mut ParseOptions,
+) -> Vec<(usize, &'a str)> {
+    let mut lines = vec![];
+    for (i, line) in strings_iter.by_ref() {
+        let line = line.trim();
+        if line.is_empty() {
+            options.trace(i + 1, "end of section");
+            break;
+        }
+
+        options.trace(i + 1, &format!("[{}]", line));
+        lines.push((i + 1, line));
+    }
+    lines
+}
+
+// reads one comma separated line of clues, each one is turned into a T by clue()
+pub(crate) fn parse_clue_line<T>(
+    line: usize,
+    text: &str,
+    clue: impl Fn(&str) -> Option<T>,
+) -> Result<Vec<T>, ParseError> {
+    let mut clues = vec![];
+    for c in text.split(',') {
+        match clue(c.trim()) {
+            Some(c) => clues.push(c),
+            None => {
+                return Err(ParseError::BadClue {
+                    line,
+                    text: text.to_string(),
+                })
+            }
+        }
+    }
+    Ok(clues)
+}
+
+fn parse_number(line: usize, value: &str) -> Result<usize, ParseError> {
+    value.parse().map_err(|_| ParseError::BadNumber {
+        line,
+        text: value.to_string(),
+    })
+}
+
+// the goal is written row by row from the top, just like the array.
+// each char is turned into a cell by cell()
+pub(crate) fn parse_goal<T>(
+    line: usize,
+    goal: &str,
+    width: usize,
+    height: usize,
+    cell: impl Fn(char) -> Option<T>,
+) -> Result<Vec<T>, ParseError> {
+    let mut cells = vec![];
+    for c in goal.chars() {
+        match cell(c) {
+            Some(cell) => cells.push(cell),
+            None => {
+                return Err(ParseError::BadGoal {
+                    line,
+                    text: goal.to_string(),
+                })
+            }
+        }
+    }
+    if cells.len() != width * height {
+        return Err(ParseError::GoalLengthMismatch {
+            line,
+            expected: width * height,
+            found: cells.len(),
+        });
+    }
+    Ok(cells)
+}
+
+// mirrors clues and cells of any kind top to bottom, shared by Puzzle and ColorPuzzle
+pub(crate) fn flip_vertical<C, T: Copy>(
+    row_clues: &mut [Vec<C>],
+    column_clues: &mut [Vec<C>],
+    array: &mut Vec<T>,
+    solution: &mut Option<Vec<T>>,
+    width: usize,
+) {
+    let flip = |cells: &[T]| -> Vec<T> {
+        cells
+            .chunks(width.max(1))
+            .rev()
+            .flatten()
+            .copied()
+            .collect()
+    };
+
+    row_clues.reverse();
+    for clues in column_clues.iter_mut() {
+        clues.reverse();
+    }
+    *array = flip(array);
+    if let Some(cells) = solution {
+        *cells = flip(cells);
+    }
+}
+
+pub(crate) fn unquote(value: &str) -> String {
+    value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value)
+        .to_string()
+}
+
+fn clues_to_string(clues: &[usize]) -> String {
+    clues
+        .iter()
+        .map(|clue| clue.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WEBPBN_1: &str = "catalogue \"webpbn.com #1\"\ntitle \"Demo Puzzle from Front Page\"\nby \"Jan Wolter\"\ncopyright \"© Copyright 2004 by Jan Wolter\"\nlicense CC-BY-3.0\nwidth 5\nheight 10\n\nrows\n2\n2,1\n1,1\n3\n1,1\n1,1\n2\n1,1\n1,2\n2\n\ncolumns\n2,1\n2,1,3\n7\n1,3\n2,1\n\ngoal \"01100011010010101110101001010000110010100101111000\"";
+
+    #[test]
+    fn test_metadata() {
+        let p = Puzzle::from_string(WEBPBN_1).unwrap();
+
+        assert_eq!(p.metadata.catalogue.as_deref(), Some("webpbn.com #1"));
+        assert_eq!(
+            p.metadata.title.as_deref(),
+            Some("Demo Puzzle from Front Page")
+        );
+        assert_eq!(p.metadata.by.as_deref(), Some("Jan Wolter"));
+        assert_eq!(
+            p.metadata.copyright.as_deref(),
+            Some("© Copyright 2004 by Jan Wolter")
+        );
+        assert_eq!(p.metadata.license.as_deref(), Some("CC-BY-3.0"));
+    }
+
+    #[test]
+    fn test_goal() {
+        let p = Puzzle::from_string(WEBPBN_1).unwrap();
+        let solution = p.solution.unwrap();
+
+        assert_eq!(solution.len(), 50);
+        // the first row of the goal "01100" is the top row of the array
+        assert_eq!(
+            solution[..5],
+            [
+                Cell::Empty,
+                Cell::Filled,
+                Cell::Filled,
+                Cell::Empty,
+                Cell::Empty
+            ]
+        );
+
+        assert!(Puzzle::from_string("rows\n1\n\ncolumns\n1\n\ngoal \"10\"").is_err());
+        assert!(Puzzle::from_string("rows\n1\n\ncolumns\n1\n\ngoal \"2\"").is_err());
+    }
+
+    #[test]
+    fn test_orientation() {
+        let mut p = Puzzle::from_string(WEBPBN_1).unwrap();
+        assert_eq!(p.row_clues[0], vec![2]);
+        assert_eq!(p.row_clues[1], vec![2, 1]);
+
+        // the goal is a solution of the clues, top row first
+        p.array = p.solution.clone().unwrap();
+        assert!(p.verify());
+        assert_eq!(p.get_cell(0, 0), Cell::Empty);
+        assert_eq!(p.get_cell(1, 0), Cell::Filled);
+        assert_eq!(p.get_cell(0, 9), Cell::Filled);
+
+        let options = ParseOptions {
+            flip_vertical: true,
+            ..Default::default()
+        };
+        let mut q = Puzzle::from_string_with_options(WEBPBN_1, options).unwrap();
+        assert_eq!(q.row_clues[0], vec![2]);
+        assert_eq!(q.row_clues[1], vec![1, 2]);
+        assert_eq!(q.column_clues[1], vec![3, 1, 2]);
+
+        // still a solution, just upside down
+        q.array = q.solution.clone().unwrap();
+        assert!(q.verify());
+        assert_eq!(q.get_cell(0, 0), Cell::Filled);
+        assert_eq!(q.get_cell(1, 9), Cell::Filled);
+    }
+
+    #[test]
+    fn test_dimensions() {
+        assert!(Puzzle::from_string("width 2\nrows\n1\n\ncolumns\n1\n").is_err());
+        assert!(Puzzle::from_string("height 2\nrows\n1\n\ncolumns\n1\n").is_err());
+        assert!(Puzzle::from_string("width 1\nheight 1\nrows\n1\n\ncolumns\n1\n").is_ok());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Puzzle::from_string("title \"none\"\n").err(),
+            Some(ParseError::MissingRowsSection { line: 1 })
+        );
+        assert_eq!(
+            Puzzle::from_string("rows\n1\n2\n").err(),
+            Some(ParseError::MissingColumnsSection { line: 3 })
+        );
+        assert_eq!(
+            Puzzle::from_string("rows\n1\n1,a\n\ncolumns\n1\n").err(),
+            Some(ParseError::BadClue {
+                line: 3,
+                text: "1,a".to_string()
+            })
+        );
+        assert_eq!(
+            Puzzle::from_string("width five\nrows\n1\n\ncolumns\n1\n").err(),
+            Some(ParseError::BadNumber {
+                line: 1,
+                text: "five".to_string()
+            })
+        );
+        assert_eq!(
+            Puzzle::from_string("rows\n1\n\ncolumns\n1\n\ngoal \"2\"").err(),
+            Some(ParseError::BadGoal {
+                line: 7,
+                text: "2".to_string()
+            })
+        );
+
+        let e = Puzzle::from_string("width 3\nrows\n1\n\ncolumns\n1\n")
+            .err()
+            .unwrap();
+        assert_eq!(
+            e,
+            ParseError::DimensionMismatch {
+                line: 1,
+                expected: 3,
+                found: 1
+            }
+        );
+        assert_eq!(e.line(), 1);
+        assert_eq!(e.to_string(), "line 1: expected 3 but the clues give 1");
+
+        let e = Puzzle::from_string("rows\n1\n\ncolumns\n1\n\ngoal \"10\"")
+            .err()
+            .unwrap();
+        assert_eq!(
+            e,
+            ParseError::GoalLengthMismatch {
+                line: 7,
+                expected: 1,
+                found: 2
+            }
+        );
+        assert_eq!(
+            e.to_string(),
+            "line 7: goal has 2 cells but the clues give 1"
+        );
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let mut log = vec![];
+        let mut sink = |line: usize, message: &str| log.push(format!("{}: {}", line, message));
+        let options = ParseOptions {
+            diagnostics: Some(&mut sink),
+            ..Default::default()
+        };
+        Puzzle::from_string_with_options("rows\n1\n\ncolumns\n1,1\n", options).unwrap();
+
+        assert_eq!(
+            log,
+            vec![
+                "1: rows",
+                "2: [1]",
+                "3: end of section",
+                "4: columns",
+                "5: [1,1]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let p = Puzzle::from_string(WEBPBN_1).unwrap();
+        let s = p.to_non_string();
+        let q = Puzzle::from_string(&s).unwrap();
+
+        assert_eq!(p.row_clues, q.row_clues);
+        assert_eq!(p.column_clues, q.column_clues);
+        assert_eq!(p.metadata, q.metadata);
+        assert_eq!(p.solution, q.solution);
+        assert!(s.contains("goal \"01100011010010101110101001010000110010100101111000\""));
+        assert!(s.contains("rows\n2\n2,1\n1,1\n"));
+    }
+}
@@ -0,0 +1,282 @@
+use crate::{Cell, Puzzle};
+
+// the chars used for cells and the frame
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GlyphSet {
+    // '.', '0' and '/' for cells, '|', '-' and '+' for the frame
+    Ascii,
+    // filled cells as solid blocks, '·' and '╳' for empty and crossed cells and
+    // box drawing chars for the frame
+    Unicode,
+}
+
+// where the column clues sit when some columns have fewer clues than others
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColumnClueAlign {
+    // pushed down against the grid
+    Bottom,
+    // starting from the top line
+    Top,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RenderOptions {
+    // chars per cell, widened when a column clue needs more room
+    pub cell_width: usize,
+    pub glyphs: GlyphSet,
+    // a line between every 5 rows and every 5 columns
+    pub separators: bool,
+    pub column_clue_align: ColumnClueAlign,
+}
+
+impl Default for RenderOptions {
+    // the layout Display has always used
+    fn default() -> Self {
+        RenderOptions {
+            cell_width: 3,
+            glyphs: GlyphSet::Ascii,
+            separators: false,
+            column_clue_align: ColumnClueAlign::Bottom,
+        }
+    }
+}
+
+// draws a puzzle as text: row clues on the left, column clues on top and a char per
+// cell in the grid. clues of any length get as much room as they need
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct TextRenderer {
+    options: RenderOptions,
+}
+
+impl TextRenderer {
+    pub fn new(options: RenderOptions) -> Self {
+        TextRenderer { options }
+    }
+
+    pub fn render(&self, puzzle: &Puzzle) -> String {
+        let options = &self.options;
+        let (vertical, horizontal, cross) = match options.glyphs {
+            GlyphSet::Ascii => ('|', '-', '+'),
+            GlyphSet::Unicode => ('│', '─', '┼'),
+        };
+        let digits = |clues: &[Vec<usize>]| {
+            clues
+                .iter()
+                .flatten()
+                .map(|clue| clue.to_string().len())
+                .max()
+                .unwrap_or(0)
+        };
+
+        // a row clue takes a space and its digits, a cell at least as much as its clues
+        let row_clue_width = (digits(&puzzle.row_clues) + 1).max(3);
+        // column clues start at the last char of their cell and run on into the space in
+        // front of the next one, room for the last column's has to be made past the grid
+        let overhang = puzzle
+            .column_clues
+            .last()
+            .map(|clues| digits(std::slice::from_ref(clues)).saturating_sub(1))
+            .unwrap_or(0);
+        let cell_width = options
+            .cell_width
+            .max(digits(&puzzle.column_clues) + 1)
+            .max(1);
+        let separators_before = |i: usize| if options.separators { i / 5 } else { 0 };
+
+        let clues_width = puzzle.get_longest_row_clue_len() * row_clue_width;
+        let clues_height = puzzle.get_longest_column_clue_len();
+        let grid_left = clues_width + 1;
+        let width = match puzzle.get_width() {
+            0 => grid_left,
+            w => grid_left + w * cell_width + separators_before(w - 1) + overhang,
+        };
+        let height = match puzzle.get_height() {
+            0 => clues_height + 1,
+            h => clues_height + 1 + h + separators_before(h - 1),
+        };
+        let column_start = |x: usize| grid_left + x * cell_width + separators_before(x);
+        let row_line = |y: usize| clues_height + 1 + y + separators_before(y);
+
+        let mut canvas = vec![vec![' '; width]; height];
+        let mut write = |x: usize, y: usize, text: &str| {
+            for (i, c) in text.chars().enumerate() {
+                canvas[y][x + i] = c;
+            }
+        };
+
+        // column clues
+        for (x, clues) in puzzle.column_clues.iter().enumerate() {
+            let start = column_start(x) + cell_width - 1;
+            for (j, clue) in clues.iter().enumerate() {
+                let line = match options.column_clue_align {
+                    ColumnClueAlign::Bottom => clues_height - clues.len() + j,
+                    ColumnClueAlign::Top => j,
+                };
+                write(start, line, &clue.to_string());
+            }
+        }
+
+        // row clues, a space and then the number
+        for (y, clues) in puzzle.row_clues.iter().enumerate() {
+            for (j, clue) in clues.iter().rev().enumerate() {
+                let start = clues_width - (j + 1) * row_clue_width;
+                write(start + 1, row_line(y), &clue.to_string());
+            }
+        }
+
+        // frame
+        for c in canvas[clues_height][grid_left..width - overhang].iter_mut() {
+            *c = horizontal;
+        }
+        for y in 0..puzzle.get_height() {
+            canvas[row_line(y)][clues_width] = vertical;
+            if y > 0 && y % 5 == 0 && options.separators {
+                let line = &mut canvas[row_line(y) - 1];
+                line[clues_width] = cross;
+                for c in line[grid_left..width - overhang].iter_mut() {
+                    *c = horizontal;
+                }
+            }
+        }
+        if options.separators {
+            for x in (5..puzzle.get_width()).step_by(5) {
+                let column = column_start(x) - 1;
+                for y in 0..puzzle.get_height() {
+                    canvas[row_line(y)][column] = vertical;
+                    if y > 0 && y % 5 == 0 {
+                        canvas[row_line(y) - 1][column] = cross;
+                    }
+                }
+            }
+        }
+
+        // cells, drawn in the last char of their space
+        for (pos, cell) in puzzle.array.iter().enumerate() {
+            let start = column_start(pos % puzzle.get_width());
+            let line = &mut canvas[row_line(pos / puzzle.get_width())];
+            match (options.glyphs, cell) {
+                (GlyphSet::Ascii, Cell::Empty) => line[start + cell_width - 1] = '.',
+                (GlyphSet::Ascii, Cell::Filled) => line[start + cell_width - 1] = '0',
+                (GlyphSet::Ascii, Cell::Crossed) => line[start + cell_width - 1] = '/',
+                (GlyphSet::Unicode, Cell::Empty) => line[start + cell_width - 1] = '·',
+                // blocks fill the whole cell so neighbours join up
+                (GlyphSet::Unicode, Cell::Filled) => {
+                    for c in line[start..start + cell_width].iter_mut() {
+                        *c = '█';
+                    }
+                }
+                (GlyphSet::Unicode, Cell::Crossed) => line[start + cell_width - 1] = '╳',
+            }
+        }
+
+        let mut result = String::new();
+        for line in canvas {
+            result.extend(line);
+            result.push('\n');
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::with_clues;
+
+    #[test]
+    fn test_default_layout() {
+        let mut p = Puzzle::new();
+        p.push_clues_row(vec![1, 1]);
+        p.push_clues_row(vec![2]);
+        p.push_clues_column(vec![2]);
+        p.push_clues_column(vec![1]);
+        p.push_clues_column(vec![1, 1]);
+        p.set_cell(0, 0, Cell::Filled);
+        p.set_cell(1, 0, Cell::Crossed);
+
+        let expected = concat!(
+            "               1\n",
+            "         2  1  1\n",
+            "       ---------\n",
+            " 1  1 |  0  /  .\n",
+            "    2 |  .  .  .\n",
+        );
+        assert_eq!(TextRenderer::default().render(&p), expected);
+        assert_eq!(p.to_string(), expected);
+    }
+
+    #[test]
+    fn test_long_clues() {
+        let mut p = with_clues(&[&[120, 1], &[3]], &[&[1, 1], &[14]]);
+        p.set_cell(0, 0, Cell::Filled);
+        p.set_cell(1, 1, Cell::Crossed);
+
+        // the row clues get room for 3 digits, the cells for 2 and a space
+        let expected = concat!(
+            "           1    \n",
+            "           1  14\n",
+            "         ------ \n",
+            " 120 1  |  0  . \n",
+            "     3  |  .  / \n",
+        );
+        assert_eq!(TextRenderer::default().render(&p), expected);
+
+        let top = TextRenderer::new(RenderOptions {
+            column_clue_align: ColumnClueAlign::Top,
+            ..Default::default()
+        });
+        assert!(top
+            .render(&p)
+            .starts_with("           1  14\n           1    \n"));
+
+        // the 3 digit clue widens the cells and still starts at the last char of its own
+        p.column_clues[0] = vec![100];
+        let text = TextRenderer::default().render(&p);
+        assert!(text.starts_with("            100 14\n"));
+    }
+
+    #[test]
+    fn test_two_digit_column_clues() {
+        // the same text Display gave before it was drawn with a TextRenderer
+        let mut p = Puzzle::new();
+        p.push_clues_row(vec![1, 1]);
+        p.push_clues_row(vec![2]);
+        p.push_clues_column(vec![1]);
+        p.push_clues_column(vec![14]);
+        p.push_clues_column(vec![2]);
+        p.set_cell(1, 0, Cell::Filled);
+        let expected = concat!(
+            "         1  14 2\n",
+            "       ---------\n",
+            " 1  1 |  .  0  .\n",
+            "    2 |  .  .  .\n",
+        );
+        assert_eq!(p.to_string(), expected);
+    }
+
+    #[test]
+    fn test_unicode_and_separators() {
+        let p = Puzzle::from_solution_string("111111", 6).unwrap();
+        let renderer = TextRenderer::new(RenderOptions {
+            cell_width: 2,
+            glyphs: GlyphSet::Unicode,
+            separators: true,
+            ..Default::default()
+        });
+        let expected = concat!(
+            "     1 1 1 1 1  1\n",
+            "    ─────────────\n",
+            " 6 │██████████│██\n",
+        );
+        assert_eq!(renderer.render(&p), expected);
+
+        let p = Puzzle::from_solution_grid(vec![Cell::Empty; 7], 1).unwrap();
+        let renderer = TextRenderer::new(RenderOptions {
+            separators: true,
+            ..Default::default()
+        });
+        let text = renderer.render(&p);
+        assert_eq!(text.lines().count(), 2 + 7 + 1);
+        assert_eq!(text.lines().nth(7), Some("   +---"));
+    }
+}
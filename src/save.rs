@@ -0,0 +1,317 @@
+use crate::{Cell, Puzzle};
+
+// save states start with these bytes followed by the version
+const MAGIC: &[u8; 3] = b"PXS";
+const VERSION: u8 = 1;
+// magic, version, width, height and clue hash
+const HEADER_LEN: usize = 3 + 1 + 4 + 4 + 4;
+const CHECKSUM_LEN: usize = 4;
+
+// why a save state couldn't be made or loaded into a puzzle
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum SaveError {
+    // shorter than the header and checksum, or the cells are cut off
+    Truncated,
+    // doesn't start with the save state magic bytes
+    BadMagic,
+    // written by a newer version of the format
+    UnsupportedVersion(u8),
+    // the bytes were changed after saving
+    BadChecksum,
+    // saved for a puzzle of a different size, as (width, height)
+    DimensionMismatch {
+        expected: (usize, usize),
+        found: (usize, usize),
+    },
+    // saved for a puzzle of the same size but with other clues
+    ClueMismatch,
+    // a cell with the unused 2 bit value
+    BadCell,
+    // a string that isn't base64
+    BadEncoding,
+    // the puzzle is too wide or high for the header
+    TooLarge {
+        width: usize,
+        height: usize,
+    },
+}
+
+impl std::fmt::Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Truncated => write!(f, "Save state is truncated"),
+            SaveError::BadMagic => write!(f, "Not a save state"),
+            SaveError::UnsupportedVersion(v) => write!(f, "Unsupported save state version {}", v),
+            SaveError::BadChecksum => write!(f, "Save state checksum does not match"),
+            SaveError::DimensionMismatch { expected, found } => write!(
+                f,
+                "Save state is for a {}x{} puzzle, not {}x{}",
+                found.0, found.1, expected.0, expected.1
+            ),
+            SaveError::ClueMismatch => write!(f, "Save state is for a puzzle with other clues"),
+            SaveError::BadCell => write!(f, "Save state contains an invalid cell"),
+            SaveError::BadEncoding => write!(f, "Save state is not valid base64"),
+            SaveError::TooLarge { width, height } => {
+                write!(f, "A {}x{} puzzle is too large to save", width, height)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl Puzzle {
+    // packs the array into a small versioned save state, 2 bits per cell, tied to these
+    // clues with a hash and protected by a checksum. fails if the width or height
+    // doesn't fit in the header
+    pub fn save_state(&self) -> Result<Vec<u8>, SaveError> {
+        let too_large = || SaveError::TooLarge {
+            width: self.get_width(),
+            height: self.get_height(),
+        };
+        let width = u32::try_from(self.get_width()).map_err(|_| too_large())?;
+        let height = u32::try_from(self.get_height()).map_err(|_| too_large())?;
+
+        let mut bytes =
+            Vec::with_capacity(HEADER_LEN + self.array.len().div_ceil(4) + CHECKSUM_LEN);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&width.to_le_bytes());
+        bytes.extend_from_slice(&height.to_le_bytes());
+        bytes.extend_from_slice(&self.clue_hash().to_le_bytes());
+
+        for cells in self.array.chunks(4) {
+            let mut byte = 0;
+            for (i, cell) in cells.iter().enumerate() {
+                let bits = match cell {
+                    Cell::Empty => 0,
+                    Cell::Filled => 1,
+                    Cell::Crossed => 2,
+                };
+                byte |= bits << (i * 2);
+            }
+            bytes.push(byte);
+        }
+
+        let checksum = fnv1a(&bytes);
+        bytes.extend_from_slice(&checksum.to_le_bytes());
+        Ok(bytes)
+    }
+
+    // replaces the array with a save state made by save_state(), the puzzle is left
+    // untouched if the state is damaged or was saved for another puzzle
+    pub fn load_state(&mut self, bytes: &[u8]) -> Result<(), SaveError> {
+        if bytes.len() < HEADER_LEN + CHECKSUM_LEN {
+            return Err(SaveError::Truncated);
+        }
+        if &bytes[..3] != MAGIC {
+            return Err(SaveError::BadMagic);
+        }
+        if bytes[3] != VERSION {
+            return Err(SaveError::UnsupportedVersion(bytes[3]));
+        }
+
+        let (data, checksum) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+        if fnv1a(data).to_le_bytes() != checksum {
+            return Err(SaveError::BadChecksum);
+        }
+
+        let width = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as usize;
+        let height = u32::from_le_bytes([data[8], data[9], data[10], data[11]]) as usize;
+        if (width, height) != (self.get_width(), self.get_height()) {
+            return Err(SaveError::DimensionMismatch {
+                expected: (self.get_width(), self.get_height()),
+                found: (width, height),
+            });
+        }
+        let hash = u32::from_le_bytes([data[12], data[13], data[14], data[15]]);
+        if hash != self.clue_hash() {
+            return Err(SaveError::ClueMismatch);
+        }
+
+        let packed = &data[HEADER_LEN..];
+        if packed.len() != (width * height).div_ceil(4) {
+            return Err(SaveError::Truncated);
+        }
+        let mut array = Vec::with_capacity(width * height);
+        for i in 0..width * height {
+            let bits = (packed[i / 4] >> ((i % 4) * 2)) & 0b11;
+            array.push(match bits {
+                0 => Cell::Empty,
+                1 => Cell::Filled,
+                2 => Cell::Crossed,
+                _ => return Err(SaveError::BadCell),
+            });
+        }
+
+        self.array = array;
+        self.history.clear();
+        Ok(())
+    }
+
+    // save_state() as base64, for places like localStorage that only hold strings
+    pub fn save_state_string(&self) -> Result<String, SaveError> {
+        Ok(base64_encode(&self.save_state()?))
+    }
+
+    pub fn load_state_string(&mut self, string: &str) -> Result<(), SaveError> {
+        let bytes = base64_decode(string).ok_or(SaveError::BadEncoding)?;
+        self.load_state(&bytes)
+    }
+
+    // identifies the clues so a save state isn't loaded into another puzzle of the same size
+    fn clue_hash(&self) -> u32 {
+        let mut bytes = vec![];
+        for clues in self.row_clues.iter().chain(self.column_clues.iter()) {
+            for clue in clues {
+                bytes.extend_from_slice(&(*clue as u32).to_le_bytes());
+            }
+            // separates the lines so [1],[1,1] and [1,1],[1] hash differently
+            bytes.extend_from_slice(&u32::MAX.to_le_bytes());
+        }
+        fnv1a(&bytes)
+    }
+}
+
+// 32 bit FNV-1a, small and good enough to catch damaged or mismatched saves
+fn fnv1a(bytes: &[u8]) -> u32 {
+    let mut hash: u32 = 0x811c9dc5;
+    for byte in bytes {
+        hash ^= *byte as u32;
+        hash = hash.wrapping_mul(0x01000193);
+    }
+    hash
+}
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                result.push(BASE64[(n >> (18 - i * 6)) as usize & 0x3f] as char);
+            } else {
+                result.push('=');
+            }
+        }
+    }
+    result
+}
+
+fn base64_decode(string: &str) -> Option<Vec<u8>> {
+    let string = string.trim().trim_end_matches('=');
+    let mut result = Vec::with_capacity(string.len() * 3 / 4);
+    let mut n: u32 = 0;
+    let mut bits = 0;
+    for c in string.bytes() {
+        let value = BASE64.iter().position(|b| *b == c)? as u32;
+        n = n << 6 | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            result.push((n >> bits) as u8);
+        }
+    }
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let mut p = Puzzle::from_solution_string("0101001010000001000101110", 5).unwrap();
+        p.array = vec![Cell::Empty; 25];
+        let empty = p.clone();
+        p.set_cell(1, 0, Cell::Filled);
+        p.set_cell(0, 0, Cell::Crossed);
+        p.set_cell(4, 4, Cell::Filled);
+        let bytes = p.save_state().unwrap();
+        // 16 header bytes, 7 bytes of cells and the checksum
+        assert_eq!(bytes.len(), 27);
+
+        let mut q = empty.clone();
+        q.load_state(&bytes).unwrap();
+        assert_eq!(p.array, q.array);
+
+        let mut q = empty;
+        q.load_state_string(&p.save_state_string().unwrap())
+            .unwrap();
+        assert_eq!(p.array, q.array);
+
+        // wider than a u16 can hold
+        let mut p = Puzzle::from_solution_grid(vec![Cell::Empty; 70000], 70000).unwrap();
+        p.set_cell(69999, 0, Cell::Crossed);
+        let bytes = p.save_state().unwrap();
+        let mut q = p.clone();
+        q.array = vec![Cell::Empty; 70000];
+        q.load_state(&bytes).unwrap();
+        assert_eq!(p.array, q.array);
+    }
+
+    #[test]
+    fn test_load_errors() {
+        let mut p = Puzzle::from_solution_string("0101001010000001000101110", 5).unwrap();
+        p.array = vec![Cell::Empty; 25];
+        let bytes = p.save_state().unwrap();
+        let mut q = p.clone();
+
+        assert_eq!(q.load_state(&bytes[..10]), Err(SaveError::Truncated));
+        assert_eq!(
+            q.load_state(b"nope nope nope nope nope"),
+            Err(SaveError::BadMagic)
+        );
+
+        let mut damaged = bytes.clone();
+        damaged[16] ^= 1;
+        assert_eq!(q.load_state(&damaged), Err(SaveError::BadChecksum));
+
+        let mut newer = bytes.clone();
+        newer[3] = 2;
+        assert_eq!(q.load_state(&newer), Err(SaveError::UnsupportedVersion(2)));
+
+        let mut other = Puzzle::from_solution_string("0110", 2).unwrap();
+        assert_eq!(
+            other.load_state(&bytes),
+            Err(SaveError::DimensionMismatch {
+                expected: (2, 2),
+                found: (5, 5)
+            })
+        );
+
+        let mut other = p.clone();
+        other.row_clues[0] = vec![2];
+        assert_eq!(other.load_state(&bytes), Err(SaveError::ClueMismatch));
+        assert_eq!(
+            q.load_state_string("not base64!"),
+            Err(SaveError::BadEncoding)
+        );
+
+        // a puzzle too big for the header isn't saved at all, which can't be built in a test
+        assert_eq!(
+            SaveError::TooLarge {
+                width: 1,
+                height: usize::MAX
+            }
+            .to_string(),
+            format!("A 1x{} puzzle is too large to save", usize::MAX)
+        );
+
+        // nothing was loaded along the way
+        assert_eq!(q.array, vec![Cell::Empty; 25]);
+    }
+
+    #[test]
+    fn test_base64() {
+        for bytes in [&b""[..], b"f", b"fo", b"foo", b"foob", b"fooba", b"foobar"] {
+            assert_eq!(base64_decode(&base64_encode(bytes)).unwrap(), bytes);
+        }
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+    }
+}
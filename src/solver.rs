@@ -0,0 +1,722 @@
+use crate::{Cell, Puzzle};
+
+// how much work a call to the solver took
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct SolveStats {
+    pub solved: bool,
+    // passes over the queued rows and then the queued columns
+    pub sweeps: usize,
+    // single row or column runs of the line solver
+    pub line_solves: usize,
+    // cells tried both ways to look for a contradiction
+    pub probes: usize,
+    // cells the backtracking search had to guess
+    pub guesses: usize,
+}
+
+// returned when the clues can't be satisfied by any grid
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Contradiction;
+
+impl std::fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The clues contradict each other")
+    }
+}
+
+impl std::error::Error for Contradiction {}
+
+// how a single row or column is solved
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum LineSolver {
+    // only the cells the left-most and right-most placements agree on, see overlap()
+    Overlap,
+    // everything the line can tell, see settle()
+    Settle,
+}
+
+// rows and columns that still need to be run through the line solver
+pub(crate) struct LineQueue {
+    rows: Vec<bool>,
+    columns: Vec<bool>,
+}
+
+impl LineQueue {
+    pub(crate) fn all(height: usize, width: usize) -> Self {
+        LineQueue {
+            rows: vec![true; height],
+            columns: vec![true; width],
+        }
+    }
+
+    // only the row and the column crossing the cell at pos
+    pub(crate) fn crossing(height: usize, width: usize, pos: usize) -> Self {
+        let mut queue = LineQueue {
+            rows: vec![false; height],
+            columns: vec![false; width],
+        };
+        queue.rows[pos / width] = true;
+        queue.columns[pos % width] = true;
+        queue
+    }
+
+    fn is_empty(&self) -> bool {
+        !self
+            .rows
+            .iter()
+            .chain(self.columns.iter())
+            .any(|dirty| *dirty)
+    }
+}
+
+impl Puzzle {
+    // solves as much of the current puzzle as line logic allows,
+    // returns true if puzzle was completable by the solver
+    pub fn solve(&mut self) -> bool {
+        self.solve_with_stats().solved
+    }
+
+    // same as solve() but also reports how many sweeps and line solves it took
+    pub fn solve_with_stats(&mut self) -> SolveStats {
+        let mut stats = SolveStats::default();
+        let mut queue = LineQueue::all(self.get_height(), self.get_width());
+
+        if self.propagate(&mut queue, &mut stats) {
+            stats.solved = self.verify();
+        }
+        stats
+    }
+
+    // solves the puzzle completely, falling back to probing and guessing when line logic
+    // stalls. on success the array holds the solution, on failure it is left untouched
+    pub fn solve_full(&mut self) -> Result<(), Contradiction> {
+        self.solve_full_with_stats().map(|_| ())
+    }
+
+    // same as solve_full() but also reports how much work it took
+    pub fn solve_full_with_stats(&mut self) -> Result<SolveStats, Contradiction> {
+        let original = self.array.clone();
+        let mut stats = SolveStats::default();
+        let mut solutions = self.search_from_scratch(1, &mut stats);
+
+        match solutions.pop() {
+            Some(solution) => {
+                self.array = solution;
+                stats.solved = true;
+                Ok(stats)
+            }
+            None => {
+                self.array = original;
+                Err(Contradiction)
+            }
+        }
+    }
+
+    // counts the solutions of the clues, stopping once limit is reached.
+    // 0 means the clues are contradictory, limit means "at least limit"
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.find_solutions(limit).len()
+    }
+
+    // true if the clues describe exactly one picture
+    pub fn is_unique(&self) -> bool {
+        self.count_solutions(2) == 1
+    }
+
+    // collects up to limit different solutions of the clues, handy for showing
+    // an author two pictures that share the same clues
+    pub fn find_solutions(&self, limit: usize) -> Vec<Vec<Cell>> {
+        if limit == 0 {
+            return vec![];
+        }
+        let mut p = self.clone();
+        p.array = vec![Cell::Empty; self.array.len()];
+        p.search_from_scratch(limit, &mut SolveStats::default())
+    }
+
+    // line solves every row and column before handing over to search()
+    fn search_from_scratch(&mut self, limit: usize, stats: &mut SolveStats) -> Vec<Vec<Cell>> {
+        let mut solutions = vec![];
+        let mut queue = LineQueue::all(self.get_height(), self.get_width());
+
+        if self.propagate(&mut queue, stats) {
+            self.search(limit, stats, &mut solutions);
+        }
+        solutions
+    }
+
+    // depth first search over the empty cells, the array is restored from a copy once a
+    // guess has been explored. every full grid passing verify() is collected until limit
+    pub(crate) fn search(
+        &mut self,
+        limit: usize,
+        stats: &mut SolveStats,
+        solutions: &mut Vec<Vec<Cell>>,
+    ) {
+        loop {
+            match self.probe(stats) {
+                Some(true) => continue,
+                Some(false) => break,
+                None => return,
+            }
+        }
+
+        let pos = match self.array.iter().position(|cell| *cell == Cell::Empty) {
+            Some(pos) => pos,
+            None => {
+                if self.verify() {
+                    solutions.push(self.array.clone());
+                }
+                return;
+            }
+        };
+
+        stats.guesses += 1;
+        for guess in [Cell::Filled, Cell::Crossed] {
+            let saved = self.array.clone();
+            if self.try_cell(pos, guess, stats) {
+                self.search(limit, stats, solutions);
+            }
+            self.array = saved;
+
+            if solutions.len() >= limit {
+                return;
+            }
+        }
+    }
+
+    // tries every empty cell both ways, a value that leads to a contradiction means the
+    // cell must be the other one. Some(true) if a cell was solved, None if neither works
+    pub(crate) fn probe(&mut self, stats: &mut SolveStats) -> Option<bool> {
+        let mut change_made = false;
+
+        for pos in 0..self.array.len() {
+            if self.array[pos] != Cell::Empty {
+                continue;
+            }
+            stats.probes += 1;
+
+            for (guess, other) in [(Cell::Filled, Cell::Crossed), (Cell::Crossed, Cell::Filled)] {
+                let saved = self.array.clone();
+                let possible = self.try_cell(pos, guess, stats);
+                self.array = saved;
+
+                if !possible {
+                    if !self.try_cell(pos, other, stats) {
+                        return None;
+                    }
+                    change_made = true;
+                    break;
+                }
+            }
+        }
+        Some(change_made)
+    }
+
+    // sets one cell and runs the line solver from there, false on a contradiction
+    fn try_cell(&mut self, pos: usize, cell: Cell, stats: &mut SolveStats) -> bool {
+        self.array[pos] = cell;
+        let mut queue = LineQueue::crossing(self.get_height(), self.get_width(), pos);
+        self.propagate(&mut queue, stats)
+    }
+
+    // runs the line solver over queued lines until none are left, every changed cell
+    // queues the line crossing it. returns false if a line turned out to be impossible
+    pub(crate) fn propagate(&mut self, queue: &mut LineQueue, stats: &mut SolveStats) -> bool {
+        self.propagate_with(queue, stats, LineSolver::Settle)
+    }
+
+    // same as propagate() with a choice of line solver
+    pub(crate) fn propagate_with(
+        &mut self,
+        queue: &mut LineQueue,
+        stats: &mut SolveStats,
+        solver: LineSolver,
+    ) -> bool {
+        let width = self.get_width();
+        let height = self.get_height();
+
+        while !queue.is_empty() {
+            stats.sweeps += 1;
+
+            // for each queued row, its cells sit next to each other in array
+            for y in 0..queue.rows.len() {
+                if !queue.rows[y] {
+                    continue;
+                }
+                queue.rows[y] = false;
+                stats.line_solves += 1;
+
+                let clues = &self.row_clues[y];
+                match solve_line(&mut self.array, y * width, 1, width, clues, solver) {
+                    Ok(changed) => changed.into_iter().for_each(|x| queue.columns[x] = true),
+                    Err(Contradiction) => return false,
+                }
+            }
+            // for each queued column, one cell every width cells
+            for x in 0..queue.columns.len() {
+                if !queue.columns[x] {
+                    continue;
+                }
+                queue.columns[x] = false;
+                stats.line_solves += 1;
+
+                let clues = &self.column_clues[x];
+                match solve_line(&mut self.array, x, width, height, clues, solver) {
+                    Ok(changed) => changed.into_iter().for_each(|y| queue.rows[y] = true),
+                    Err(Contradiction) => return false,
+                }
+            }
+        }
+        true
+    }
+}
+
+// Solves the len cells of array at start, start + step, start + 2 * step... using given
+// clues, so a row or a column is read and written in place without touching the rest of
+// the grid. Returns the positions in the line of the changed cells, an error if the clues
+// can not be placed in the cells at all
+pub(crate) fn solve_line(
+    array: &mut [Cell],
+    start: usize,
+    step: usize,
+    len: usize,
+    clues: &[usize],
+    solver: LineSolver,
+) -> Result<Vec<usize>, Contradiction> {
+    let line: Vec<Cell> = (0..len).map(|i| array[start + i * step]).collect();
+    let solved = match solver {
+        LineSolver::Overlap => overlap(&line, clues).ok_or(Contradiction)?,
+        LineSolver::Settle => settle(&line, clues)?,
+    };
+
+    let mut changed = vec![];
+    for (i, (old, new)) in line.into_iter().zip(solved).enumerate() {
+        if old != new {
+            array[start + i * step] = new;
+            changed.push(i);
+        }
+    }
+    Ok(changed)
+}
+
+// Mathematical Approach
+// https://en.wikipedia.org/wiki/Nonogram#Mathematical_approach
+// every clue is pushed as far left and as far right as the line allows, cells covered
+// by both placements of a clue are filled and cells no clue can reach are crossed
+pub(crate) fn overlap(line: &[Cell], clues: &[usize]) -> Option<Vec<Cell>> {
+    let blocks = blocks(clues);
+    let left = leftmost(line, &blocks)?;
+    let right = rightmost(line, &blocks)?;
+
+    let mut result = line.to_vec();
+    let mut reachable = vec![false; line.len()];
+    for (j, len) in blocks.iter().enumerate() {
+        for cell in result.iter_mut().take(left[j] + len).skip(right[j]) {
+            *cell = Cell::Filled;
+        }
+        for r in reachable.iter_mut().take(right[j] + len).skip(left[j]) {
+            *r = true;
+        }
+    }
+    for (cell, r) in result.iter_mut().zip(reachable) {
+        if !r {
+            if *cell == Cell::Filled {
+                return None;
+            }
+            *cell = Cell::Crossed;
+        }
+    }
+    Some(result)
+}
+
+// The exact line solver, also known as settling a line. Finds every placement of the
+// clues that agrees with the line, a cell no placement fills is crossed and a cell every
+// placement fills is filled. Unlike overlap() nothing a single line could tell is missed,
+// e.g. 0X1X0 from 00100 with clues 1 1
+pub(crate) fn settle(line: &[Cell], clues: &[usize]) -> Result<Vec<Cell>, Contradiction> {
+    settle_by(line.len(), |i| line[i], clues)
+}
+
+// settle() on a line of n cells read through cell(i), so other layouts like the bit
+// planes of a BitGrid share the same solver without copying their lines out first
+pub(crate) fn settle_by(
+    n: usize,
+    cell: impl Fn(usize) -> Cell,
+    clues: &[usize],
+) -> Result<Vec<Cell>, Contradiction> {
+    let blocks = blocks(clues);
+    let k = blocks.len();
+
+    // crossed[i] is the number of crossed cells among the first i
+    let mut crossed = vec![0; n + 1];
+    for i in 0..n {
+        crossed[i + 1] = crossed[i] + (cell(i) == Cell::Crossed) as usize;
+    }
+    let no_cross = |s: usize, e: usize| crossed[e] == crossed[s];
+
+    // before[j][i] is true when blocks[..j] can be placed in the first i cells
+    let mut before = vec![vec![false; n + 1]; k + 1];
+    before[0][0] = true;
+    for i in 1..=n {
+        before[0][i] = before[0][i - 1] && cell(i - 1) != Cell::Filled;
+    }
+    for j in 1..=k {
+        let len = blocks[j - 1];
+        for i in 1..=n {
+            // cell i - 1 left empty
+            let empty = before[j][i - 1] && cell(i - 1) != Cell::Filled;
+            // block j - 1 ends at i, with a gap in front of it unless it starts the line
+            let ends_here = i >= len
+                && no_cross(i - len, i)
+                && match i - len {
+                    0 => j == 1,
+                    s => cell(s - 1) != Cell::Filled && before[j - 1][s - 1],
+                };
+            before[j][i] = empty || ends_here;
+        }
+    }
+
+    // after[j][i] is true when blocks[j..] can be placed in the cells from i on
+    let mut after = vec![vec![false; n + 1]; k + 1];
+    after[k][n] = true;
+    for i in (0..n).rev() {
+        after[k][i] = after[k][i + 1] && cell(i) != Cell::Filled;
+    }
+    for j in (0..k).rev() {
+        let len = blocks[j];
+        for i in (0..n).rev() {
+            let empty = after[j][i + 1] && cell(i) != Cell::Filled;
+            let starts_here = i + len <= n
+                && no_cross(i, i + len)
+                && match i + len {
+                    e if e == n => j + 1 == k,
+                    e => cell(e) != Cell::Filled && after[j + 1][e + 1],
+                };
+            after[j][i] = empty || starts_here;
+        }
+    }
+    if !after[0][0] {
+        return Err(Contradiction);
+    }
+
+    // a cell can be empty if the blocks before and after it fit around it
+    let can_be_empty: Vec<bool> = (0..n)
+        .map(|i| cell(i) != Cell::Filled && (0..=k).any(|j| before[j][i] && after[j][i + 1]))
+        .collect();
+    // a cell can be filled if some placement of a block covers it, counted with a
+    // running sum over the start and end of every placement
+    let mut covered = vec![0isize; n + 1];
+    for (j, len) in blocks.iter().enumerate() {
+        for s in 0..=n.saturating_sub(*len) {
+            let e = s + len;
+            let fits_before = match s {
+                0 => j == 0,
+                s => cell(s - 1) != Cell::Filled && before[j][s - 1],
+            };
+            let fits_after = match e {
+                e if e == n => j + 1 == k,
+                e => cell(e) != Cell::Filled && after[j + 1][e + 1],
+            };
+            if no_cross(s, e) && fits_before && fits_after {
+                covered[s] += 1;
+                covered[e] -= 1;
+            }
+        }
+    }
+
+    let mut result: Vec<Cell> = (0..n).map(&cell).collect();
+    let mut count = 0;
+    for i in 0..n {
+        count += covered[i];
+        result[i] = match (count > 0, can_be_empty[i]) {
+            (true, true) => result[i],
+            (true, false) => Cell::Filled,
+            (false, true) => Cell::Crossed,
+            (false, false) => return Err(Contradiction),
+        };
+    }
+    Ok(result)
+}
+
+// clues with the "0" entry of an empty line removed
+pub(crate) fn blocks(clues: &[usize]) -> Vec<usize> {
+    clues.iter().copied().filter(|c| *c > 0).collect()
+}
+
+// start position of every block in the left-most legal placement, None if there is none
+pub(crate) fn leftmost(line: &[Cell], blocks: &[usize]) -> Option<Vec<usize>> {
+    let n = line.len();
+    let k = blocks.len();
+
+    // crossed[i] is the number of crossed cells in line[..i]
+    let mut crossed = vec![0; n + 1];
+    for (i, cell) in line.iter().enumerate() {
+        crossed[i + 1] = crossed[i] + (*cell == Cell::Crossed) as usize;
+    }
+    // a block of len can start at s without covering a cross or touching a filled cell
+    let can_place = |s: usize, len: usize| {
+        s + len <= n
+            && crossed[s + len] == crossed[s]
+            && (s + len == n || line[s + len] != Cell::Filled)
+    };
+    let next = |s: usize, len: usize| (s + len + 1).min(n);
+
+    // fits[j][i] is true when blocks[j..] can be placed in line[i..]
+    let mut fits = vec![vec![false; n + 1]; k + 1];
+    fits[k][n] = true;
+    for i in (0..n).rev() {
+        fits[k][i] = line[i] != Cell::Filled && fits[k][i + 1];
+    }
+    for j in (0..k).rev() {
+        for i in (0..n).rev() {
+            fits[j][i] = (can_place(i, blocks[j]) && fits[j + 1][next(i, blocks[j])])
+                || (line[i] != Cell::Filled && fits[j][i + 1]);
+        }
+    }
+    if !fits[0][0] {
+        return None;
+    }
+
+    // walk the table taking the first start that still leaves room for the rest
+    let mut starts = Vec::with_capacity(k);
+    let mut pos = 0;
+    for (j, len) in blocks.iter().enumerate() {
+        let mut s = pos;
+        while !(can_place(s, *len) && fits[j + 1][next(s, *len)]) {
+            s += 1;
+        }
+        starts.push(s);
+        pos = next(s, *len);
+    }
+    Some(starts)
+}
+
+// start position of every block in the right-most legal placement, None if there is none
+pub(crate) fn rightmost(line: &[Cell], blocks: &[usize]) -> Option<Vec<usize>> {
+    let n = line.len();
+    let reversed_line: Vec<Cell> = line.iter().rev().copied().collect();
+    let reversed_blocks: Vec<usize> = blocks.iter().rev().copied().collect();
+    let starts = leftmost(&reversed_line, &reversed_blocks)?;
+
+    Some(
+        starts
+            .iter()
+            .zip(reversed_blocks.iter())
+            .rev()
+            .map(|(s, len)| n - s - len)
+            .collect(),
+    )
+}
+
+// a line of cells from '1' for filled, 'X' for crossed and anything else for empty,
+// for the tests of every module
+#[cfg(test)]
+pub(crate) fn line(s: &str) -> Vec<Cell> {
+    s.chars()
+        .map(|c| match c {
+            '1' => Cell::Filled,
+            'X' => Cell::Crossed,
+            _ => Cell::Empty,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_overlap_empty_line() {
+        assert_eq!(overlap(&line("0000000000"), &[8]), Some(line("0011111100")));
+        assert_eq!(overlap(&line("00000"), &[0]), Some(line("XXXXX")));
+        assert_eq!(overlap(&line("00000"), &[1, 3]), Some(line("1X111")));
+    }
+
+    #[test]
+    fn test_solve_line() {
+        // the middle column of a 3x3 grid
+        let mut array = line("000000000");
+        assert_eq!(
+            solve_line(&mut array, 1, 3, 3, &[3], LineSolver::Settle),
+            Ok(vec![0, 1, 2])
+        );
+        assert_eq!(array, line("010010010"));
+        assert_eq!(
+            solve_line(&mut array, 1, 3, 3, &[3], LineSolver::Settle),
+            Ok(vec![])
+        );
+
+        // the last row
+        assert_eq!(
+            solve_line(&mut array, 6, 1, 3, &[0], LineSolver::Settle),
+            Err(Contradiction)
+        );
+        assert_eq!(
+            solve_line(&mut array, 6, 1, 3, &[1], LineSolver::Settle),
+            Ok(vec![0, 2])
+        );
+        assert_eq!(array, line("010010X1X"));
+
+        let mut array = line("00100");
+        assert_eq!(
+            solve_line(&mut array, 0, 1, 5, &[1, 1], LineSolver::Overlap),
+            Ok(vec![])
+        );
+        assert_eq!(
+            solve_line(&mut array, 0, 1, 5, &[1, 1], LineSolver::Settle),
+            Ok(vec![1, 3])
+        );
+    }
+
+    #[test]
+    fn test_settle() {
+        // overlap can't tell which of the two blocks the filled cell belongs to
+        assert_eq!(overlap(&line("00100"), &[1, 1]), Some(line("00100")));
+        assert_eq!(settle(&line("00100"), &[1, 1]), Ok(line("0X1X0")));
+
+        assert_eq!(settle(&line("0000000000"), &[8]), Ok(line("0011111100")));
+        assert_eq!(settle(&line("00000"), &[0]), Ok(line("XXXXX")));
+        assert_eq!(settle(&line("0X0000"), &[3]), Ok(line("XX0110")));
+        assert_eq!(settle(&line("0000100000"), &[2]), Ok(line("XXX010XXXX")));
+        assert_eq!(settle(&line("1X1X0"), &[1, 1]), Ok(line("1X1XX")));
+        assert_eq!(settle(&line(""), &[0]), Ok(line("")));
+
+        assert_eq!(settle(&line("111X0"), &[2]), Err(Contradiction));
+        assert_eq!(settle(&line("0X0X0"), &[2]), Err(Contradiction));
+        assert_eq!(settle(&line("01010"), &[0]), Err(Contradiction));
+    }
+
+    #[test]
+    fn test_overlap_partial_line() {
+        // a cross pushes the block to the right
+        assert_eq!(overlap(&line("0X0000"), &[3]), Some(line("XX0110")));
+        // a filled cell pulls the block towards itself
+        assert_eq!(overlap(&line("0000100000"), &[2]), Some(line("XXX010XXXX")));
+        // a complete clue crosses the rest of the line
+        assert_eq!(overlap(&line("0111000"), &[3]), Some(line("X111XXX")));
+        assert_eq!(overlap(&line("01X00"), &[1, 1]), Some(line("X1X00")));
+    }
+
+    #[test]
+    fn test_overlap_contradiction() {
+        assert_eq!(overlap(&line("00X00"), &[3]), None);
+        assert_eq!(overlap(&line("1X1X1"), &[1, 1]), None);
+        assert_eq!(overlap(&line("00100"), &[0]), None);
+    }
+
+    #[test]
+    fn test_solve_full() {
+        // line logic stalls on the bottom two rows of this one
+        let mut p = Puzzle::new();
+        p.push_clues_row(vec![1, 1]);
+        p.push_clues_row(vec![1, 1]);
+        p.push_clues_row(vec![0]);
+        p.push_clues_row(vec![1, 1]);
+        p.push_clues_row(vec![3]);
+        p.push_clues_column(vec![1]);
+        p.push_clues_column(vec![2, 1]);
+        p.push_clues_column(vec![1]);
+        p.push_clues_column(vec![2, 1]);
+        p.push_clues_column(vec![1]);
+        assert!(!p.solve());
+
+        let stats = p.solve_full_with_stats().unwrap();
+        assert!(stats.solved);
+        assert!(stats.probes > 0);
+        assert!(p.verify());
+        assert_eq!(p.array, line("X1X1XX1X1XXXXXX1XXX1X111X"));
+    }
+
+    #[test]
+    fn test_solve_full_guess() {
+        // every solution is as good as the next, so this needs a guess
+        let mut p = Puzzle::new();
+        p.push_clues_row(vec![1]);
+        p.push_clues_row(vec![1]);
+        p.push_clues_column(vec![1]);
+        p.push_clues_column(vec![1]);
+
+        let stats = p.solve_full_with_stats().unwrap();
+        assert_eq!(stats.guesses, 1);
+        assert!(p.verify());
+    }
+
+    #[test]
+    fn test_solve_full_contradiction() {
+        let mut p = Puzzle::new();
+        p.push_clues_row(vec![2]);
+        p.push_clues_row(vec![0]);
+        p.push_clues_column(vec![1]);
+        p.push_clues_column(vec![0]);
+        p.array[0] = Cell::Filled;
+
+        assert_eq!(p.solve_full(), Err(Contradiction));
+        // the array is left the way it was
+        assert_eq!(p.array, line("1000"));
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let mut p = Puzzle::new();
+        p.push_clues_row(vec![1, 1]);
+        p.push_clues_row(vec![1, 1]);
+        p.push_clues_row(vec![0]);
+        p.push_clues_row(vec![1, 1]);
+        p.push_clues_row(vec![3]);
+        p.push_clues_column(vec![1]);
+        p.push_clues_column(vec![2, 1]);
+        p.push_clues_column(vec![1]);
+        p.push_clues_column(vec![2, 1]);
+        p.push_clues_column(vec![1]);
+        assert_eq!(p.count_solutions(10), 1);
+        assert!(p.is_unique());
+
+        // the top two rows can swap their cells around
+        p.column_clues = vec![vec![1], vec![1, 2], vec![1], vec![1, 2], vec![1]];
+        assert_eq!(p.count_solutions(10), 4);
+        assert_eq!(p.count_solutions(2), 2);
+        assert!(!p.is_unique());
+
+        let solutions = p.find_solutions(2);
+        assert_ne!(solutions[0], solutions[1]);
+        for solution in solutions {
+            p.array = solution;
+            assert!(p.verify());
+        }
+
+        p.row_clues[2] = vec![5];
+        assert_eq!(p.count_solutions(10), 0);
+        assert_eq!(p.count_solutions(0), 0);
+    }
+
+    #[test]
+    fn test_solve_stats() {
+        let mut p = Puzzle::new();
+        p.push_clues_row(vec![3]);
+        p.push_clues_row(vec![2, 2]);
+        p.push_clues_row(vec![5]);
+        p.push_clues_row(vec![1, 1]);
+        p.push_clues_row(vec![1, 1]);
+        p.push_clues_column(vec![4]);
+        p.push_clues_column(vec![3]);
+        p.push_clues_column(vec![1, 1]);
+        p.push_clues_column(vec![3]);
+        p.push_clues_column(vec![4]);
+        let solution = line("X111X11X11111111XXX11XXX1");
+
+        let stats = p.solve_with_stats();
+        assert!(stats.solved);
+        assert_eq!(p.array, solution);
+        // first sweep touches every line once, later sweeps only the dirty ones
+        assert!(stats.sweeps > 1);
+        assert!(stats.line_solves >= 10);
+        assert!(stats.line_solves < stats.sweeps * 10);
+
+        // nothing left to change, so one sweep over every line
+        let stats = p.solve_with_stats();
+        assert!(stats.solved);
+        assert_eq!(stats.sweeps, 1);
+        assert_eq!(stats.line_solves, 10);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::solver::{blocks, leftmost, rightmost};
+use crate::{Cell, Puzzle};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LineState {
+    // the filled cells match the clues exactly
+    Complete,
+    // not done yet, but the clues can still be placed
+    Possible,
+    // the filled and crossed cells can't be matched to the clues any more
+    Contradictory,
+}
+
+// how far along a row or column is, for greying out and striking through clues
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LineStatus {
+    pub state: LineState,
+    // one entry per clue, true once the clue's block is filled in and can't be anywhere else
+    pub satisfied_clues: Vec<bool>,
+}
+
+impl Puzzle {
+    pub fn row_status(&self, y: usize) -> LineStatus {
+        line_status(self.row(y), &self.row_clues[y])
+    }
+
+    pub fn column_status(&self, x: usize) -> LineStatus {
+        let cells: Vec<Cell> = self.column(x).copied().collect();
+        line_status(&cells, &self.column_clues[x])
+    }
+
+    pub fn row_statuses(&self) -> Vec<LineStatus> {
+        (0..self.get_height()).map(|y| self.row_status(y)).collect()
+    }
+
+    pub fn column_statuses(&self) -> Vec<LineStatus> {
+        (0..self.get_width())
+            .map(|x| self.column_status(x))
+            .collect()
+    }
+}
+
+fn line_status(cells: &[Cell], clues: &[usize]) -> LineStatus {
+    let complete = blocks(&Puzzle::build_clues(cells)) == blocks(clues);
+    let blocks = blocks(clues);
+
+    let (left, right) = match (leftmost(cells, &blocks), rightmost(cells, &blocks)) {
+        (Some(left), Some(right)) => (left, right),
+        _ => {
+            return LineStatus {
+                state: LineState::Contradictory,
+                satisfied_clues: vec![false; clues.len()],
+            }
+        }
+    };
+
+    // a block is done when it can only go in one place and all of it is filled
+    let mut done = blocks.iter().enumerate().map(|(j, len)| {
+        left[j] == right[j]
+            && cells[left[j]..left[j] + len]
+                .iter()
+                .all(|c| *c == Cell::Filled)
+    });
+    let satisfied_clues = clues
+        .iter()
+        .map(|clue| match clue {
+            0 => complete,
+            _ => done.next().unwrap_or(false),
+        })
+        .collect();
+
+    LineStatus {
+        state: if complete {
+            LineState::Complete
+        } else {
+            LineState::Possible
+        },
+        satisfied_clues,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::line;
+
+    #[test]
+    fn test_line_status() {
+        let status = line_status(&line("01010"), &[1, 1]);
+        assert_eq!(status.state, LineState::Complete);
+        assert_eq!(status.satisfied_clues, vec![true, true]);
+
+        let status = line_status(&line("X1X000"), &[1, 2]);
+        assert_eq!(status.state, LineState::Possible);
+        assert_eq!(status.satisfied_clues, vec![true, false]);
+
+        // the 2 could be either clue, so nothing is struck through yet
+        let status = line_status(&line("000110000"), &[2, 2]);
+        assert_eq!(status.satisfied_clues, vec![false, false]);
+
+        let status = line_status(&line("111X0"), &[2]);
+        assert_eq!(status.state, LineState::Contradictory);
+        assert_eq!(status.satisfied_clues, vec![false]);
+
+        assert_eq!(line_status(&line("XXX"), &[0]).satisfied_clues, vec![true]);
+        assert_eq!(
+            line_status(&line("X1X"), &[0]).state,
+            LineState::Contradictory
+        );
+    }
+
+    #[test]
+    fn test_puzzle_status() {
+        let mut p = Puzzle::new();
+        p.push_clues_row(vec![2]);
+        p.push_clues_row(vec![1]);
+        p.push_clues_column(vec![2]);
+        p.push_clues_column(vec![1]);
+
+        p.set_cell(0, 0, Cell::Filled);
+        p.set_cell(1, 0, Cell::Filled);
+        p.set_cell(1, 1, Cell::Filled);
+
+        let rows = p.row_statuses();
+        assert_eq!(rows[0].state, LineState::Complete);
+        assert_eq!(rows[1].state, LineState::Complete);
+
+        let columns = p.column_statuses();
+        assert_eq!(columns[0].state, LineState::Possible);
+        assert_eq!(columns[1].state, LineState::Contradictory);
+    }
+}
@@ -0,0 +1,201 @@
+use crate::{Cell, Puzzle};
+
+// what is drawn inside the grid of an SVG
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SvgCells {
+    // an empty grid for printing
+    Blank,
+    // the filled and crossed cells of Puzzle.array
+    Array,
+    // the filled cells of Puzzle.solution, blank if the solution isn't known
+    Solution,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SvgOptions {
+    // width and height of a cell in pixels, clue numbers take one cell each
+    pub cell_size: usize,
+    pub cells: SvgCells,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions {
+            cell_size: 20,
+            cells: SvgCells::Blank,
+        }
+    }
+}
+
+impl Puzzle {
+    // draws the puzzle as a printable SVG, row clues on the left, column clues on top
+    // and a thicker line every 5 cells
+    pub fn to_svg(&self, options: &SvgOptions) -> String {
+        let size = options.cell_size;
+        let left = self.get_longest_row_clue_len() * size;
+        let top = self.get_longest_column_clue_len() * size;
+        let grid_width = self.get_width() * size;
+        let grid_height = self.get_height() * size;
+        let width = left + grid_width + 1;
+        let height = top + grid_height + 1;
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+            w = width,
+            h = height
+        );
+        if let Some(title) = &self.metadata.title {
+            svg.push_str(&format!("<title>{}</title>\n", escape(title)));
+        }
+        svg.push_str(&format!(
+            "<rect width=\"{}\" height=\"{}\" fill=\"white\"/>\n",
+            width, height
+        ));
+
+        // cells
+        let cells = match options.cells {
+            SvgCells::Blank => None,
+            SvgCells::Array => Some(&self.array),
+            SvgCells::Solution => self.solution.as_ref(),
+        };
+        for (pos, cell) in cells.into_iter().flatten().enumerate() {
+            let x = left + pos % self.get_width() * size;
+            let y = top + pos / self.get_width() * size;
+            match cell {
+                Cell::Empty => {}
+                Cell::Filled => svg.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" fill=\"black\"/>\n",
+                    x,
+                    y,
+                    s = size
+                )),
+                Cell::Crossed => {
+                    let inset = size / 4;
+                    let (x1, y1, x2, y2) =
+                        (x + inset, y + inset, x + size - inset, y + size - inset);
+                    svg.push_str(&format!(
+                        "<path d=\"M{} {}L{} {}M{} {}L{} {}\" stroke=\"gray\" stroke-width=\"2\"/>\n",
+                        x1, y1, x2, y2, x1, y2, x2, y1
+                    ));
+                }
+            }
+        }
+
+        // clues, pushed against the grid
+        svg.push_str(&format!(
+            "<g font-family=\"sans-serif\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">\n",
+            size * 3 / 5
+        ));
+        for (y, clues) in self.row_clues.iter().enumerate() {
+            for (j, clue) in clues.iter().rev().enumerate() {
+                svg.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\">{}</text>\n",
+                    left - j * size - size / 2,
+                    top + y * size + size / 2,
+                    clue
+                ));
+            }
+        }
+        for (x, clues) in self.column_clues.iter().enumerate() {
+            for (j, clue) in clues.iter().rev().enumerate() {
+                svg.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\">{}</text>\n",
+                    left + x * size + size / 2,
+                    top - j * size - size / 2,
+                    clue
+                ));
+            }
+        }
+        svg.push_str("</g>\n");
+
+        // grid lines, thicker on the border and every 5 cells
+        let stroke = |i: usize, count: usize| {
+            if i.is_multiple_of(5) || i == count {
+                2
+            } else {
+                1
+            }
+        };
+        for x in 0..=self.get_width() {
+            let px = left + x * size;
+            svg.push_str(&format!(
+                "<line x1=\"{px}\" y1=\"{}\" x2=\"{px}\" y2=\"{}\" stroke=\"black\" stroke-width=\"{}\"/>\n",
+                top,
+                top + grid_height,
+                stroke(x, self.get_width()),
+                px = px
+            ));
+        }
+        for y in 0..=self.get_height() {
+            let py = top + y * size;
+            svg.push_str(&format!(
+                "<line x1=\"{}\" y1=\"{py}\" x2=\"{}\" y2=\"{py}\" stroke=\"black\" stroke-width=\"{}\"/>\n",
+                left,
+                left + grid_width,
+                stroke(y, self.get_height()),
+                py = py
+            ));
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+// makes text safe to put between SVG tags
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_svg() {
+        let mut p = Puzzle::from_solution_string("0101001010000001000101110", 5).unwrap();
+        p.array = vec![Cell::Empty; 25];
+        p.set_cell(0, 0, Cell::Crossed);
+        p.metadata.title = Some("Cats & dogs".to_string());
+
+        let blank = p.to_svg(&SvgOptions::default());
+        assert!(blank.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"141\""));
+        assert!(blank.contains("<title>Cats &amp; dogs</title>"));
+        assert!(!blank.contains("fill=\"black\""));
+        // 6 vertical and 6 horizontal lines, the outer ones thick
+        assert_eq!(blank.matches("<line").count(), 12);
+        assert_eq!(blank.matches("stroke-width=\"2\"/>").count(), 4);
+        // every clue is a text, the first row clue sits right next to the grid
+        let clue_count: usize = p
+            .row_clues
+            .iter()
+            .chain(&p.column_clues)
+            .map(Vec::len)
+            .sum();
+        assert_eq!(blank.matches("<text").count(), clue_count);
+        assert!(blank.contains("<text x=\"30\" y=\"50\">1</text>"));
+
+        let solution = p.to_svg(&SvgOptions {
+            cell_size: 10,
+            cells: SvgCells::Solution,
+        });
+        assert_eq!(solution.matches("fill=\"black\"").count(), 9);
+
+        let array = p.to_svg(&SvgOptions {
+            cell_size: 10,
+            cells: SvgCells::Array,
+        });
+        assert_eq!(array.matches("fill=\"black\"").count(), 0);
+        assert_eq!(array.matches("<path").count(), 1);
+    }
+
+    #[test]
+    fn test_guide_lines() {
+        let p = Puzzle::from_solution_grid(vec![Cell::Empty; 12 * 2], 12).unwrap();
+        let svg = p.to_svg(&SvgOptions::default());
+        // vertical lines at 0, 5, 10 and 12 plus the top and bottom of the grid
+        assert_eq!(svg.matches("stroke-width=\"2\"/>").count(), 6);
+    }
+}